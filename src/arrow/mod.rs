@@ -0,0 +1,397 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Support for fetching query results as Arrow `RecordBatch`es. This module is only available when
+//! the `arrow` feature is enabled.
+//!
+//! Each column is defined into a variable sized by the fetch array size of the statement, and
+//! `Statement::fetch_rows()` fills the whole array at once, so each batch holds up to that many
+//! rows. The schema is derived from the query metadata:
+//!
+//! * `NUMBER(p, s)` is a `Decimal128(p, s)`, and an unconstrained `NUMBER` or a `FLOAT` a
+//! `Float64`.
+//! * `BINARY_FLOAT` and `BINARY_DOUBLE` are `Float32` and `Float64`.
+//! * `DATE` is a `Timestamp` in seconds, and `TIMESTAMP` a `Timestamp` in microseconds, or
+//! nanoseconds if its fractional seconds precision is greater than 6.
+//! * `TIMESTAMP WITH TIME ZONE` and `TIMESTAMP WITH LOCAL TIME ZONE` are converted to UTC and
+//! given the time zone `+00:00`.
+//! * `VARCHAR2`, `NVARCHAR2`, `CHAR` and `NCHAR` are `Utf8`.
+//! * `RAW` is `Binary`.
+//! * `BOOLEAN` is `Boolean`.
+//!
+//! Columns of any other type cannot be fetched as Arrow.
+use arrow_crate::array::{
+    ArrayRef, BinaryArray, BooleanArray, Decimal128Array, Float32Array, Float64Array, StringArray,
+    TimestampMicrosecondArray, TimestampNanosecondArray, TimestampSecondArray,
+};
+use arrow_crate::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow_crate::record_batch::RecordBatch;
+use chrono::NaiveDate;
+use connection::Connection;
+use data::TypeInfo;
+use error::{Error, ErrorKind, Result};
+use odpi::enums::{ODPINativeTypeNum, ODPIOracleTypeNum};
+use odpi::structs::{ODPIData, ODPITimestamp};
+use statement::Statement;
+use std::slice;
+use std::str;
+use std::sync::Arc;
+use variable::Var;
+
+/// The time zone of timestamps converted to UTC.
+const UTC: &str = "+00:00";
+
+/// The largest fractional seconds precision that fits in a timestamp in microseconds.
+const MICROSECOND_PRECISION: u8 = 6;
+
+/// The scale reported for `NUMBER` and `FLOAT` columns without a fixed scale.
+const UNCONSTRAINED_SCALE: i8 = -127;
+
+/// Get the Arrow data type a column of the given type is fetched as.
+pub fn arrow_type(type_info: &TypeInfo) -> Result<DataType> {
+    let data_type = match type_info.oracle_type_num() {
+        ODPIOracleTypeNum::Number => {
+            let precision = type_info.precision();
+            let scale = type_info.scale();
+
+            if precision > 0
+                && precision <= 38
+                && scale != UNCONSTRAINED_SCALE
+                && i16::from(scale) <= precision
+            {
+                DataType::Decimal128(precision as u8, scale)
+            } else {
+                DataType::Float64
+            }
+        }
+        ODPIOracleTypeNum::NativeFloat => DataType::Float32,
+        ODPIOracleTypeNum::NativeDouble => DataType::Float64,
+        ODPIOracleTypeNum::Date => DataType::Timestamp(TimeUnit::Second, None),
+        ODPIOracleTypeNum::Timestamp => DataType::Timestamp(time_unit(type_info), None),
+        ODPIOracleTypeNum::TimestampTz | ODPIOracleTypeNum::TimestampLtz => {
+            DataType::Timestamp(time_unit(type_info), Some(UTC.into()))
+        }
+        ODPIOracleTypeNum::Varchar
+        | ODPIOracleTypeNum::NVarchar
+        | ODPIOracleTypeNum::Char
+        | ODPIOracleTypeNum::NChar => DataType::Utf8,
+        ODPIOracleTypeNum::Raw => DataType::Binary,
+        ODPIOracleTypeNum::Boolean => DataType::Boolean,
+        other => {
+            return Err(ErrorKind::FromSql(other.to_string(), "Arrow".to_string()).into());
+        }
+    };
+
+    Ok(data_type)
+}
+
+/// Get the unit of a timestamp column, given its fractional seconds precision.
+fn time_unit(type_info: &TypeInfo) -> TimeUnit {
+    if type_info.fs_precision() > MICROSECOND_PRECISION {
+        TimeUnit::Nanosecond
+    } else {
+        TimeUnit::Microsecond
+    }
+}
+
+/// Get the Arrow schema of an executed query.
+pub fn schema(stmt: &Statement) -> Result<Schema> {
+    let num_cols = stmt.get_num_query_columns()?;
+    let mut fields = Vec::with_capacity(num_cols as usize);
+
+    for pos in 1..=num_cols {
+        let info = stmt.get_query_info(pos)?;
+        let data_type = arrow_type(&info.type_info())?;
+        fields.push(Field::new(info.name(), data_type, info.null_ok()));
+    }
+
+    Ok(Schema::new(fields))
+}
+
+/// A column of the query, and the variable its values are fetched into.
+struct Column {
+    /// The variable the column is defined into.
+    var: Var,
+    /// The Oracle type of the column.
+    oracle_type_num: ODPIOracleTypeNum,
+    /// The Arrow type the column is fetched as.
+    data_type: DataType,
+}
+
+/// An iterator over the results of a query as Arrow `RecordBatch`es, created with
+/// `Connection::query_arrow()` or `RecordBatches::new()`. Each batch holds up to the fetch array
+/// size of the statement.
+pub struct RecordBatches {
+    /// The statement being fetched.
+    stmt: Statement,
+    /// The schema of each batch.
+    schema: SchemaRef,
+    /// The columns of the query.
+    columns: Vec<Column>,
+    /// The number of rows fetched at a time.
+    array_size: u32,
+    /// Have all the rows been fetched?
+    done: bool,
+}
+
+impl RecordBatches {
+    /// Define the columns of an executed query so its rows can be fetched as `RecordBatch`es.
+    ///
+    /// * `conn` - the connection the statement was prepared on, used to create the variables the
+    /// columns are defined into.
+    /// * `stmt` - the executed query.
+    pub fn new(conn: &Connection, stmt: Statement) -> Result<Self> {
+        let schema = schema(&stmt)?;
+        let array_size = stmt.get_fetch_array_size()?;
+        let mut columns = Vec::with_capacity(schema.fields().len());
+
+        for (idx, field) in schema.fields().iter().enumerate() {
+            let pos = idx as u32 + 1;
+            let type_info = stmt.get_query_info(pos)?.type_info();
+            let oracle_type_num = type_info.oracle_type_num();
+            let (native_type_num, size, size_is_bytes) = match *field.data_type() {
+                DataType::Decimal128(..) | DataType::Utf8 => {
+                    (ODPINativeTypeNum::Bytes, type_info.size_in_chars(), false)
+                }
+                DataType::Binary => (ODPINativeTypeNum::Bytes, type_info.db_size_in_bytes(), true),
+                DataType::Float32 => (ODPINativeTypeNum::Float, 0, false),
+                DataType::Float64 => (ODPINativeTypeNum::Double, 0, false),
+                DataType::Timestamp(..) => (ODPINativeTypeNum::Timestamp, 0, false),
+                _ => (ODPINativeTypeNum::Boolean, 0, false),
+            };
+
+            let var = conn.new_var(
+                oracle_type_num,
+                native_type_num,
+                array_size,
+                size,
+                size_is_bytes,
+                false,
+            )?;
+            stmt.define(pos, &var)?;
+            columns.push(Column {
+                var,
+                oracle_type_num,
+                data_type: field.data_type().clone(),
+            });
+        }
+
+        Ok(Self {
+            stmt,
+            schema: Arc::new(schema),
+            columns,
+            array_size,
+            done: false,
+        })
+    }
+
+    /// Get the schema of each batch.
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Fetch the next batch of rows, if any remain.
+    fn fetch_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let (buffer_row_index, num_rows, more_rows) = self.stmt.fetch_rows(self.array_size)?;
+        if !more_rows {
+            self.done = true;
+        }
+        if num_rows == 0 {
+            return Ok(None);
+        }
+
+        let start = buffer_row_index as usize;
+        let end = start + num_rows as usize;
+        let mut arrays = Vec::with_capacity(self.columns.len());
+
+        for column in &self.columns {
+            let data = column.var.get_data()?;
+            let data = data.get(start..end).ok_or(ErrorKind::Conversion)?;
+            arrays.push(to_array(column, data)?);
+        }
+
+        RecordBatch::try_new(self.schema.clone(), arrays)
+            .map(Some)
+            .map_err(|e| ErrorKind::Arrow(e.to_string()).into())
+    }
+}
+
+impl Iterator for RecordBatches {
+    type Item = Result<RecordBatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.fetch_batch() {
+            Ok(Some(batch)) => Some(Ok(batch)),
+            Ok(None) => None,
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Build an Arrow array from the fetched values of a column.
+fn to_array(column: &Column, data: &[ODPIData]) -> Result<ArrayRef> {
+    let array: ArrayRef = match column.data_type {
+        DataType::Decimal128(precision, scale) => {
+            let values = collect(data, |data| {
+                let text = str::from_utf8(bytes(data)).map_err(|_| ErrorKind::Conversion)?;
+                parse_decimal(text, scale)
+            })?;
+            Arc::new(
+                Decimal128Array::from(values)
+                    .with_precision_and_scale(precision, scale)
+                    .map_err(|e| Error::from(ErrorKind::Arrow(e.to_string())))?,
+            )
+        }
+        DataType::Float32 => Arc::new(Float32Array::from(collect(data, |data| unsafe {
+            Ok(data.value.as_float)
+        })?)),
+        DataType::Float64 => Arc::new(Float64Array::from(collect(data, |data| unsafe {
+            Ok(data.value.as_double)
+        })?)),
+        DataType::Timestamp(ref unit, ref tz) => {
+            let utc = match column.oracle_type_num {
+                ODPIOracleTypeNum::TimestampTz | ODPIOracleTypeNum::TimestampLtz => true,
+                _ => false,
+            };
+            let values = collect(data, |data| {
+                epoch_nanos(unsafe { &data.value.as_timestamp }, utc)
+            })?;
+
+            match *unit {
+                TimeUnit::Second => Arc::new(
+                    TimestampSecondArray::from(scale_values(values, 1_000_000_000))
+                        .with_timezone_opt(tz.clone()),
+                ),
+                TimeUnit::Microsecond => Arc::new(
+                    TimestampMicrosecondArray::from(scale_values(values, 1_000))
+                        .with_timezone_opt(tz.clone()),
+                ),
+                _ => Arc::new(TimestampNanosecondArray::from(values).with_timezone_opt(tz.clone())),
+            }
+        }
+        DataType::Utf8 => {
+            let values = collect(data, |data| {
+                str::from_utf8(bytes(data)).map_err(|_| ErrorKind::Conversion.into())
+            })?;
+            Arc::new(StringArray::from(values))
+        }
+        DataType::Binary => Arc::new(BinaryArray::from(collect(data, |data| Ok(bytes(data)))?)),
+        _ => Arc::new(BooleanArray::from(collect(data, |data| unsafe {
+            Ok(data.value.as_boolean != 0)
+        })?)),
+    };
+
+    Ok(array)
+}
+
+/// Convert each fetched value that is not null.
+fn collect<'a, T, F>(data: &'a [ODPIData], f: F) -> Result<Vec<Option<T>>>
+where
+    F: Fn(&'a ODPIData) -> Result<T>,
+{
+    data.iter()
+        .map(|data| {
+            if data.is_null == 0 {
+                f(data).map(Some)
+            } else {
+                Ok(None)
+            }
+        })
+        .collect()
+}
+
+/// Get the bytes of a value fetched as `ODPINativeTypeNum::Bytes`.
+fn bytes(data: &ODPIData) -> &[u8] {
+    let bytes = unsafe { data.value.as_bytes };
+    if bytes.ptr.is_null() || bytes.length == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(bytes.ptr as *const u8, bytes.length as usize) }
+    }
+}
+
+/// Divide each timestamp in nanoseconds by the number of nanoseconds in a unit.
+fn scale_values(values: Vec<Option<i64>>, nanos_per_unit: i64) -> Vec<Option<i64>> {
+    values
+        .into_iter()
+        .map(|value| value.map(|nanos| nanos.div_euclid(nanos_per_unit)))
+        .collect()
+}
+
+/// Get the nanoseconds since the Unix epoch of an ODPI-C timestamp. The time zone is ignored unless
+/// `utc` is set, in which case the timestamp is converted to UTC.
+fn epoch_nanos(timestamp: &ODPITimestamp, utc: bool) -> Result<i64> {
+    let naive = NaiveDate::from_ymd_opt(
+        i32::from(timestamp.year),
+        u32::from(timestamp.month),
+        u32::from(timestamp.day),
+    )
+    .and_then(|date| {
+        date.and_hms_nano_opt(
+            u32::from(timestamp.hour),
+            u32::from(timestamp.minute),
+            u32::from(timestamp.second),
+            timestamp.fsecond,
+        )
+    })
+    .ok_or(ErrorKind::Conversion)?;
+
+    let offset = if utc {
+        i64::from(timestamp.tz_hour_offset) * 3600 + i64::from(timestamp.tz_minute_offset) * 60
+    } else {
+        0
+    };
+
+    (naive.and_utc().timestamp() - offset)
+        .checked_mul(1_000_000_000)
+        .and_then(|nanos| nanos.checked_add(i64::from(naive.and_utc().timestamp_subsec_nanos())))
+        .ok_or_else(|| ErrorKind::Conversion.into())
+}
+
+/// Parse a number fetched as text into an integer scaled by 10 to the power of `scale`, the
+/// representation of a `Decimal128` value.
+#[doc(hidden)]
+pub fn parse_decimal(text: &str, scale: i8) -> Result<i128> {
+    let (negative, unsigned) = if text.starts_with('-') {
+        (true, &text[1..])
+    } else {
+        (false, text)
+    };
+    let (whole, fraction) = match unsigned.find('.') {
+        Some(idx) => (&unsigned[..idx], &unsigned[idx + 1..]),
+        None => (unsigned, ""),
+    };
+
+    let digits = format!("{}{}", whole, fraction);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ErrorKind::Conversion.into());
+    }
+
+    let value: i128 = digits.parse().map_err(|_| ErrorKind::Conversion)?;
+    let shift = i32::from(scale) - fraction.len() as i32;
+    let value = if shift >= 0 {
+        10_i128
+            .checked_pow(shift as u32)
+            .and_then(|factor| value.checked_mul(factor))
+    } else {
+        10_i128
+            .checked_pow(-shift as u32)
+            .filter(|factor| value % factor == 0)
+            .map(|factor| value / factor)
+    }
+    .ok_or(ErrorKind::Conversion)?;
+
+    Ok(if negative { -value } else { value })
+}
@@ -0,0 +1,244 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Authentication methods used when creating standalone connections and session pools. An `Auth`
+//! is validated before any call is made to the database, and maps onto the username, password,
+//! `external_auth` and `auth_mode` arguments expected by ODPI-C.
+use error::{Error, ErrorKind, Result};
+use odpi::flags::{self, ODPIAuthMode};
+
+/// The method used to authenticate a connection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Auth {
+    /// Authenticate with a database username and password.
+    Password {
+        /// The name of the user.
+        user: String,
+        /// The password of the user.
+        password: String,
+    },
+    /// Authenticate externally, using operating system credentials or an Oracle wallet.
+    External,
+    /// Authenticate as `user` and open a session as `proxy_user`, which must have been granted
+    /// `CONNECT THROUGH user`. This is the `user[proxy_user]` form of the username.
+    Proxy {
+        /// The name of the user that authenticates.
+        user: String,
+        /// The password of the user that authenticates.
+        password: String,
+        /// The name of the user the session is opened as.
+        proxy_user: String,
+    },
+    /// Authenticate with an administrative privilege, such as SYSDBA. Without credentials the
+    /// connection is authenticated by the operating system (i.e. `/ as sysdba`).
+    Privileged {
+        /// The name of the user, or None for operating system authentication.
+        user: Option<String>,
+        /// The password of the user, or None for operating system authentication.
+        password: Option<String>,
+        /// The privilege(s) to authenticate with.
+        mode: ODPIAuthMode,
+    },
+}
+
+impl Auth {
+    /// Classify the raw username, password, external authentication and authorization mode
+    /// arguments used to create a connection, rejecting invalid combinations.
+    ///
+    /// * `user` - the name of the user. A name of the form `user[proxy_user]` is a proxy user.
+    /// * `password` - the password of the user.
+    /// * `external_auth` - whether external authentication is requested.
+    /// * `mode` - the authorization mode.
+    pub fn from_parts(
+        user: Option<&str>,
+        password: Option<&str>,
+        external_auth: bool,
+        mode: ODPIAuthMode,
+    ) -> Result<Self> {
+        let user = user.filter(|u| !u.is_empty());
+        let password = password.filter(|p| !p.is_empty());
+        let privileged = is_privileged(mode);
+
+        let auth =
+            match (user, password) {
+                (None, None) if external_auth && privileged => Auth::Privileged {
+                    user: None,
+                    password: None,
+                    mode,
+                },
+                (None, None) if external_auth => Auth::External,
+                (_, _) if external_auth => {
+                    return Err(invalid(
+                        "a username or password cannot be given with external authentication",
+                    ))
+                }
+                (None, None) => return Err(invalid(
+                    "a username and password are required unless external authentication is used",
+                )),
+                (Some(_), None) => return Err(invalid("a password is required for the user")),
+                (None, Some(_)) => return Err(invalid("a username is required for the password")),
+                (Some(user), Some(password)) if privileged => Auth::Privileged {
+                    user: Some(user.to_string()),
+                    password: Some(password.to_string()),
+                    mode,
+                },
+                (Some(user), Some(password)) => match split_proxy(user)? {
+                    Some((user, proxy_user)) => Auth::Proxy {
+                        user: user.to_string(),
+                        password: password.to_string(),
+                        proxy_user: proxy_user.to_string(),
+                    },
+                    None => Auth::Password {
+                        user: user.to_string(),
+                        password: password.to_string(),
+                    },
+                },
+            };
+
+        auth.validate()?;
+        Ok(auth)
+    }
+
+    /// Check that the authentication method is complete and consistent.
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            Auth::Password {
+                ref user,
+                ref password,
+            } => {
+                check_name("user", user)?;
+                check_present("password", password)
+            }
+            Auth::External => Ok(()),
+            Auth::Proxy {
+                ref user,
+                ref password,
+                ref proxy_user,
+            } => {
+                check_name("user", user)?;
+                check_present("password", password)?;
+                check_name("proxy user", proxy_user)
+            }
+            Auth::Privileged {
+                ref user,
+                ref password,
+                mode,
+            } => {
+                if !is_privileged(mode) {
+                    return Err(invalid(
+                        "privileged authentication requires an administrative privilege mode",
+                    ));
+                }
+                match (user, password) {
+                    (&None, &None) => Ok(()),
+                    (&Some(ref user), &Some(ref password)) => {
+                        check_name("user", user)?;
+                        check_present("password", password)
+                    }
+                    _ => Err(invalid(
+                        "privileged authentication requires both a username and password, or neither",
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Get the username to pass to ODPI-C, in the `user[proxy_user]` form for proxy
+    /// authentication.
+    pub fn username(&self) -> Option<String> {
+        match *self {
+            Auth::Password { ref user, .. } => Some(user.clone()),
+            Auth::External => None,
+            Auth::Proxy {
+                ref user,
+                ref proxy_user,
+                ..
+            } => Some(format!("{}[{}]", user, proxy_user)),
+            Auth::Privileged { ref user, .. } => user.clone(),
+        }
+    }
+
+    /// Get the password to pass to ODPI-C.
+    pub fn password(&self) -> Option<&str> {
+        match *self {
+            Auth::Password { ref password, .. } | Auth::Proxy { ref password, .. } => {
+                Some(password)
+            }
+            Auth::External => None,
+            Auth::Privileged { ref password, .. } => password.as_ref().map(|p| p.as_str()),
+        }
+    }
+
+    /// Returns true if external authentication should be requested.
+    pub fn external_auth(&self) -> bool {
+        match *self {
+            Auth::External => true,
+            Auth::Privileged { ref user, .. } => user.is_none(),
+            _ => false,
+        }
+    }
+
+    /// Get the authorization mode to pass to ODPI-C.
+    pub fn auth_mode(&self) -> ODPIAuthMode {
+        match *self {
+            Auth::Privileged { mode, .. } => mode,
+            _ => flags::DPI_MODE_AUTH_DEFAULT,
+        }
+    }
+}
+
+/// Does the mode include an administrative privilege?
+fn is_privileged(mode: ODPIAuthMode) -> bool {
+    mode.intersects(
+        flags::DPI_MODE_AUTH_SYSDBA
+            | flags::DPI_MODE_AUTH_SYSOPER
+            | flags::DPI_MODE_AUTH_SYSASM
+            | flags::DPI_MODE_AUTH_SYSBKP
+            | flags::DPI_MODE_AUTH_SYSDGD
+            | flags::DPI_MODE_AUTH_SYSKMT
+            | flags::DPI_MODE_AUTH_SYSRAC,
+    )
+}
+
+/// Split a `user[proxy_user]` username into its parts.
+fn split_proxy(user: &str) -> Result<Option<(&str, &str)>> {
+    match (user.find('['), user.ends_with(']')) {
+        (None, _) => Ok(None),
+        (Some(idx), true) => Ok(Some((&user[..idx], &user[idx + 1..user.len() - 1]))),
+        (Some(_), false) => Err(invalid(
+            "a proxy username must have the form user[proxy_user]",
+        )),
+    }
+}
+
+/// Check that a user name is present and does not contain proxy brackets.
+fn check_name(what: &str, name: &str) -> Result<()> {
+    check_present(what, name)?;
+    if name.contains('[') || name.contains(']') {
+        Err(invalid(&format!(
+            "the {} name cannot contain '[' or ']'",
+            what
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Check that a value is present.
+fn check_present(what: &str, value: &str) -> Result<()> {
+    if value.is_empty() {
+        Err(invalid(&format!("a {} is required", what)))
+    } else {
+        Ok(())
+    }
+}
+
+/// Create an authentication error.
+fn invalid(reason: &str) -> Error {
+    ErrorKind::Auth(reason.to_string()).into()
+}
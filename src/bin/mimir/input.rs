@@ -0,0 +1,144 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Lines typed at the prompt are collected into statements. A SQL statement ends with a `;` at the
+//! end of a line. A PL/SQL block (anything starting with `BEGIN`, `DECLARE` or `CREATE` of a
+//! procedure, function, package, trigger or type) keeps its semicolons and ends with a line
+//! holding only `/`. A `/` on its own with nothing buffered runs the last statement again.
+//!
+//! Lines starting with `\` outside of a statement are shell commands.
+//!
+//! PL/SQL blocks are detected with `migrate::is_plsql()`, so statements typed at the prompt are
+//! split the same way as migration scripts.
+use mimir::migrate::is_plsql;
+
+/// A shell command.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Describe the columns of a table or view.
+    Describe(String),
+    /// Show the help.
+    Help,
+    /// Write query results to a CSV file, or to the screen again if no file is given.
+    Output(Option<String>),
+    /// Leave the shell.
+    Quit,
+    /// Set a bind variable, or list them if no name is given.
+    Set(Option<(String, String)>),
+    /// Toggle showing the time each statement takes.
+    Timing,
+    /// Remove a bind variable.
+    Unset(String),
+    /// Toggle vertical output.
+    Vertical,
+}
+
+/// A complete piece of input.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Input {
+    /// A shell command.
+    Command(Command),
+    /// A statement to execute.
+    Statement(String),
+}
+
+/// Collects lines until a statement is complete.
+#[derive(Debug, Default)]
+pub struct Buffer {
+    /// The lines of the statement being entered.
+    lines: Vec<String>,
+    /// The last statement that was completed.
+    last: Option<String>,
+}
+
+impl Buffer {
+    /// Is a statement being entered?
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Discard the statement being entered.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Add a line, returning the input it completes, if any.
+    pub fn push(&mut self, line: &str) -> Result<Option<Input>, String> {
+        let trimmed = line.trim();
+
+        if self.lines.is_empty() {
+            if trimmed.is_empty() {
+                return Ok(None);
+            }
+            if trimmed.starts_with('\\') {
+                return parse_command(&trimmed[1..]).map(|command| Some(Input::Command(command)));
+            }
+            if trimmed.eq_ignore_ascii_case("exit") || trimmed.eq_ignore_ascii_case("quit") {
+                return Ok(Some(Input::Command(Command::Quit)));
+            }
+        }
+
+        if trimmed == "/" {
+            return if self.lines.is_empty() {
+                self.last
+                    .clone()
+                    .map(|sql| Some(Input::Statement(sql)))
+                    .ok_or_else(|| "No statement to run".to_string())
+            } else {
+                Ok(Some(self.complete(None)))
+            };
+        }
+
+        self.lines.push(line.to_string());
+        if !is_plsql(&self.lines.join("\n")) && trimmed.ends_with(';') {
+            Ok(Some(self.complete(Some(';'))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Take the buffered lines as a statement, removing the given terminator.
+    fn complete(&mut self, terminator: Option<char>) -> Input {
+        let mut sql = self.lines.join("\n").trim().to_string();
+        if let Some(terminator) = terminator {
+            sql = sql.trim_right_matches(terminator).trim_right().to_string();
+        }
+
+        self.lines.clear();
+        self.last = Some(sql.clone());
+        Input::Statement(sql)
+    }
+}
+
+/// Parse a shell command, without its leading `\`.
+pub fn parse_command(command: &str) -> Result<Command, String> {
+    let mut parts = command.splitn(2, char::is_whitespace);
+    let name = parts.next().unwrap_or("");
+    let arg = parts.next().map(str::trim).unwrap_or("");
+
+    let command = match name {
+        "d" | "describe" if !arg.is_empty() => Command::Describe(arg.to_string()),
+        "?" | "h" | "help" => Command::Help,
+        "o" | "output" if arg.is_empty() => Command::Output(None),
+        "o" | "output" => Command::Output(Some(arg.to_string())),
+        "q" | "quit" => Command::Quit,
+        "set" if arg.is_empty() => Command::Set(None),
+        "set" => {
+            let mut parts = arg.splitn(2, char::is_whitespace);
+            let name = parts.next().unwrap_or("").trim_left_matches(':');
+            let value = parts.next().map(str::trim).unwrap_or("");
+            Command::Set(Some((name.to_uppercase(), value.to_string())))
+        }
+        "timing" => Command::Timing,
+        "unset" if !arg.is_empty() => Command::Unset(arg.trim_left_matches(':').to_uppercase()),
+        "x" => Command::Vertical,
+        _ => return Err(format!("Unknown command '\\{}', try \\?", command)),
+    };
+
+    Ok(command)
+}
@@ -0,0 +1,83 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Query results are printed as a table, with a column per query column, or vertically, with a
+//! line per column of each row.
+use mimir::serde::iso8601_years_months;
+use mimir::{FromSql, Result, Value};
+
+/// Format a value for display. Nulls are shown as empty, RAW data as hexadecimal and objects and
+/// cursors as JSON.
+pub fn format_value(value: Value) -> Result<String> {
+    match value {
+        Value::Null => Ok(String::new()),
+        Value::Bytes(v) => Ok(v.iter().map(|byte| format!("{:02X}", byte)).collect()),
+        Value::Timestamp(v) => Ok(v.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+        Value::IntervalDS(v) => Ok(v.to_string()),
+        Value::IntervalYM(v) => Ok(iso8601_years_months(&v)),
+        value @ Value::Object(_) | value @ Value::Cursor(_) => value.to_json(),
+        value => String::from_sql(value),
+    }
+}
+
+/// Print rows as a table with a header line.
+pub fn print_table(headers: &[String], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|header| width(header)).collect();
+    for row in rows {
+        for (idx, field) in row.iter().enumerate() {
+            widths[idx] = widths[idx].max(width(field));
+        }
+    }
+
+    print_line(headers, &widths);
+    let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    println!("{}", rule.join("-+-"));
+    for row in rows {
+        print_line(row, &widths);
+    }
+}
+
+/// Print rows with a line per column, each row preceded by its number.
+pub fn print_vertical(headers: &[String], rows: &[Vec<String>]) {
+    let name_width = headers
+        .iter()
+        .map(|header| width(header))
+        .max()
+        .unwrap_or(0);
+
+    for (idx, row) in rows.iter().enumerate() {
+        println!("-[ RECORD {} ]-", idx + 1);
+        for (header, field) in headers.iter().zip(row) {
+            println!("{} | {}", pad(header, name_width), field);
+        }
+    }
+}
+
+/// Print one line of a table.
+fn print_line(fields: &[String], widths: &[usize]) {
+    let fields: Vec<String> = fields
+        .iter()
+        .zip(widths)
+        .map(|(field, width)| pad(field, *width))
+        .collect();
+    println!("{}", fields.join(" | ").trim_right());
+}
+
+/// Pad a field with spaces to the given width.
+fn pad(field: &str, width: usize) -> String {
+    format!(
+        "{}{}",
+        field,
+        " ".repeat(width.saturating_sub(self::width(field)))
+    )
+}
+
+/// The number of characters in a field.
+fn width(field: &str) -> usize {
+    field.chars().count()
+}
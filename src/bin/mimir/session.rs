@@ -0,0 +1,213 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A `Session` executes statements and shell commands on a connection, and holds the settings
+//! changed by the shell commands.
+use input::Command;
+use mimir::flags;
+use mimir::{check_identifier, Connection, CsvOptions, Result, Rows, Statement, ToSql, VarBuilder};
+use output::{format_value, print_table, print_vertical};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+
+/// The help shown by `\?`.
+const HELP: &str = "\
+SQL statements end with ';'. PL/SQL blocks end with a line holding only '/', and '/' on its own
+runs the last statement again. Statements are not committed until you run 'commit;'.
+
+  \\d NAME         describe the columns of a table or view
+  \\o [FILE]       write query results to a CSV file, or to the screen again
+  \\set NAME TEXT  set the bind variable :NAME (\\set alone lists them)
+  \\unset NAME     remove a bind variable
+  \\timing         toggle showing the time each statement takes
+  \\x              toggle vertical output
+  \\q              quit";
+
+/// The state of the shell.
+pub struct Session<'conn> {
+    /// The connection statements are executed on.
+    conn: &'conn Connection,
+    /// The values of the bind variables, keyed by upper case name.
+    binds: BTreeMap<String, String>,
+    /// The file query results are written to as CSV, if any.
+    output: Option<(String, BufWriter<File>)>,
+    /// Is the time each statement takes shown?
+    timing: bool,
+    /// Are rows shown vertically?
+    vertical: bool,
+}
+
+impl<'conn> Session<'conn> {
+    /// Create a new session on a connection.
+    pub fn new(conn: &'conn Connection) -> Self {
+        Self {
+            conn,
+            binds: BTreeMap::new(),
+            output: None,
+            timing: false,
+            vertical: false,
+        }
+    }
+
+    /// Run a shell command, returning false if the shell should exit.
+    pub fn run_command(&mut self, command: Command) -> Result<bool> {
+        match command {
+            Command::Describe(name) => self.describe(&name)?,
+            Command::Help => println!("{}", HELP),
+            Command::Output(None) => {
+                self.output = None;
+                println!("Writing query results to the screen");
+            }
+            Command::Output(Some(path)) => {
+                let file = BufWriter::new(File::create(&path)?);
+                println!("Writing query results to {}", path);
+                self.output = Some((path, file));
+            }
+            Command::Quit => return Ok(false),
+            Command::Set(None) => {
+                for (name, value) in &self.binds {
+                    println!(":{} = '{}'", name, value);
+                }
+            }
+            Command::Set(Some((name, value))) => {
+                self.binds.insert(name, value);
+            }
+            Command::Timing => {
+                self.timing = !self.timing;
+                println!("Timing is {}", if self.timing { "on" } else { "off" });
+            }
+            Command::Unset(name) => {
+                self.binds.remove(&name);
+            }
+            Command::Vertical => {
+                self.vertical = !self.vertical;
+                println!(
+                    "Vertical output is {}",
+                    if self.vertical { "on" } else { "off" }
+                );
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Execute a statement and show its results.
+    pub fn execute(&mut self, sql: &str) -> Result<()> {
+        let start = Instant::now();
+        let stmt = self.conn.prepare_stmt(Some(sql), None, false)?;
+        self.bind(&stmt)?;
+        let info = stmt.get_info()?;
+        stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+
+        if info.is_query() {
+            let rows = Rows::new(stmt)?;
+            self.show_rows(rows)?;
+        } else if info.is_plsql() {
+            println!("PL/SQL procedure successfully completed.");
+        } else if info.is_dml() {
+            println!("{} row(s) affected.", stmt.get_row_count()?);
+        } else {
+            println!("Statement processed.");
+        }
+
+        if self.timing {
+            let elapsed = start.elapsed();
+            println!(
+                "Elapsed: {}.{:03}s",
+                elapsed.as_secs(),
+                elapsed.subsec_millis()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Bind the values of the bind variables used by a statement.
+    fn bind(&self, stmt: &Statement) -> Result<()> {
+        let count = stmt.get_bind_count()?;
+        if count == 0 {
+            return Ok(());
+        }
+
+        for name in stmt.get_bind_names(count)? {
+            let value = self
+                .binds
+                .get(&name.to_uppercase())
+                .ok_or_else(|| format!("No value for bind variable :{}, use \\set", name))?;
+            let var = VarBuilder::default()
+                .sql_type(value.sql_type())
+                .create(self.conn)?;
+            var.set(0, value)?;
+            stmt.bind_by_name(&name, &var)?;
+        }
+
+        Ok(())
+    }
+
+    /// Show the rows of a query, or write them to the output file.
+    fn show_rows(&mut self, mut rows: Rows) -> Result<()> {
+        if let Some((ref path, ref mut file)) = self.output {
+            let count = rows.write_csv(&mut *file, &CsvOptions::default())?;
+            file.flush()?;
+            println!("{} row(s) written to {}", count, path);
+            return Ok(());
+        }
+
+        let headers = rows.column_names().to_vec();
+        let mut lines = Vec::new();
+        for row in rows {
+            let fields = row?
+                .into_values()
+                .into_iter()
+                .map(format_value)
+                .collect::<Result<Vec<String>>>()?;
+            lines.push(fields);
+        }
+
+        if self.vertical {
+            print_vertical(&headers, &lines);
+        } else {
+            print_table(&headers, &lines);
+        }
+        println!("\n{} row(s) selected.", lines.len());
+
+        Ok(())
+    }
+
+    /// Describe the columns of a table or view.
+    fn describe(&self, name: &str) -> Result<()> {
+        check_identifier(name)?;
+
+        let stmt = self
+            .conn
+            .prepare_stmt(Some(&format!("select * from {}", name)), None, false)?;
+        stmt.execute(flags::DPI_MODE_EXEC_DESCRIBE_ONLY)?;
+
+        let headers = vec!["Name".to_string(), "Null?".to_string(), "Type".to_string()];
+        let lines: Vec<Vec<String>> = stmt
+            .columns()?
+            .into_iter()
+            .map(|column| {
+                vec![
+                    column.name().clone(),
+                    if *column.nullable() {
+                        String::new()
+                    } else {
+                        "NOT NULL".to_string()
+                    },
+                    column.type_name().clone(),
+                ]
+            })
+            .collect();
+        print_table(&headers, &lines);
+
+        Ok(())
+    }
+}
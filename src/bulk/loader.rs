@@ -0,0 +1,483 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Bulk loads read CSV and insert each line into a table with a `BulkInsert`. The types of the
+//! target columns are described before loading, and each field is converted to its column's type
+//! on the client: numbers are parsed, dates and timestamps are read with
+//! `CsvOptions::date_format`, RAW data is read as hexadecimal and unquoted fields matching
+//! `CsvOptions::null_marker` are null. NUMBER columns that may not fit in an `i64` or an `f64`
+//! are checked on the client but bound as text, so the database converts them without losing
+//! precision. Lines that cannot be parsed or converted, and lines the database rejects, are
+//! reported with their line numbers and do not stop the load.
+use bulk::{BulkInsert, RowOutcome};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use connection::Connection;
+use error::{ErrorKind, Result};
+use odpi::enums::ODPIOracleTypeNum;
+use odpi::flags;
+use row::csv::CsvOptions;
+use std::io::{BufRead, BufReader, Read};
+use types::{SqlType, ToSql};
+use util::check_identifier;
+use variable::Var;
+
+/// The size of VARCHAR2 variables used to load CLOB columns.
+const MAX_VARCHAR_SIZE: u32 = 32_767;
+
+/// The largest precision of an integer NUMBER column that always fits in an `i64`.
+const MAX_INT64_PRECISION: i16 = 18;
+
+/// The size of VARCHAR2 variables used to load NUMBER columns as text, enough for 40 digits with a
+/// sign, decimal point and exponent.
+const NUMBER_TEXT_SIZE: u32 = 64;
+
+/// A line that was not loaded.
+#[derive(Clone, Debug, Eq, Getters, PartialEq)]
+pub struct RejectedLine {
+    /// The line number, starting from 1. For a record spanning several lines, this is the line
+    /// the record starts on.
+    #[get = "pub"]
+    line: u64,
+    /// Why the line was rejected.
+    #[get = "pub"]
+    reason: String,
+}
+
+/// The result of a bulk load.
+#[derive(Clone, Debug, Default, Eq, Getters, PartialEq)]
+pub struct LoadSummary {
+    /// The number of lines loaded.
+    #[get = "pub"]
+    loaded: u64,
+    /// The lines that were not loaded, in the order they were read.
+    #[get = "pub"]
+    rejected: Vec<RejectedLine>,
+}
+
+impl LoadSummary {
+    /// Record the outcome of each line buffered since the last flush.
+    fn record(&mut self, lines: &mut Vec<u64>, outcomes: Vec<RowOutcome>) {
+        for (line, outcome) in lines.drain(..).zip(outcomes) {
+            match outcome {
+                RowOutcome::Success(_) => self.loaded += 1,
+                RowOutcome::Failed(info) => self.reject(line, info.message().to_string()),
+            }
+        }
+    }
+
+    /// Record a rejected line.
+    fn reject(&mut self, line: u64, reason: String) {
+        self.rejected.push(RejectedLine { line, reason });
+    }
+}
+
+/// Loads CSV into tables.
+pub struct BulkLoader<'conn> {
+    /// The connection the data is loaded on.
+    conn: &'conn Connection,
+    /// The options used to read the CSV.
+    options: CsvOptions,
+}
+
+impl<'conn> BulkLoader<'conn> {
+    /// Create a new `BulkLoader` that reads CSV with the default options.
+    ///
+    /// * `conn` - the connection the data is loaded on.
+    pub fn new(conn: &'conn Connection) -> Self {
+        Self::with_options(conn, CsvOptions::default())
+    }
+
+    /// Create a new `BulkLoader` that reads CSV with the given options.
+    ///
+    /// * `conn` - the connection the data is loaded on.
+    /// * `options` - the options used to read the CSV.
+    pub fn with_options(conn: &'conn Connection, options: CsvOptions) -> Self {
+        Self { conn, options }
+    }
+
+    /// Get the options used to read the CSV.
+    pub fn options(&self) -> &CsvOptions {
+        &self.options
+    }
+
+    /// Load CSV into a table, `CsvOptions::array_size` lines at a time. The load is not committed.
+    /// Errors executing the insert, as opposed to errors for individual lines, stop the load.
+    ///
+    /// * `reader` - the CSV to load.
+    /// * `table` - the table to insert into, optionally qualified with a schema.
+    /// * `column_mapping` - the column each field is inserted into, in the order of the fields.
+    pub fn from_csv<R: Read>(
+        &self,
+        reader: R,
+        table: &str,
+        column_mapping: &[&str],
+    ) -> Result<LoadSummary> {
+        check_identifier(table)?;
+        for column in column_mapping {
+            check_identifier(column)?;
+        }
+
+        let columns = column_mapping.join(", ");
+        let column_types = self.describe(table, &columns)?;
+        let placeholders = (1..=column_types.len())
+            .map(|pos| format!(":{}", pos))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let sql = format!(
+            "insert into {} ({}) values ({})",
+            table, columns, placeholders
+        );
+        let sql_types = column_types
+            .iter()
+            .map(|column_type| column_type.sql_type())
+            .collect::<Vec<SqlType>>();
+        let mut bulk =
+            BulkInsert::with_array_size(self.conn, &sql, &sql_types, *self.options.array_size())?;
+
+        let mut records = Records::new(reader, &self.options);
+        let mut summary = LoadSummary::default();
+        let mut lines = Vec::new();
+
+        if *self.options.has_headers() {
+            records.next_record()?;
+        }
+
+        while let Some((line, record)) = records.next_record()? {
+            let fields = match record.and_then(|fields| self.convert(&fields, &column_types)) {
+                Ok(fields) => fields,
+                Err(reason) => {
+                    summary.reject(line, reason);
+                    continue;
+                }
+            };
+
+            let row = fields
+                .iter()
+                .map(|field| field as &dyn ToSql)
+                .collect::<Vec<_>>();
+            let pending = bulk.pending();
+            match bulk.add_row(&row) {
+                Ok(_) => lines.push(line),
+                // The line was buffered, so the error came from executing the buffered lines.
+                Err(e) if bulk.pending() != pending => return Err(e),
+                Err(e) => summary.reject(line, e.to_string()),
+            }
+
+            if bulk.pending() == 0 {
+                summary.record(&mut lines, bulk.flush()?);
+            }
+        }
+        summary.record(&mut lines, bulk.flush()?);
+
+        Ok(summary)
+    }
+
+    /// Describe the type of each column being loaded.
+    fn describe(&self, table: &str, columns: &str) -> Result<Vec<ColumnType>> {
+        let sql = format!("select {} from {} where 1 = 0", columns, table);
+        let stmt = self.conn.prepare_stmt(Some(&sql), None, false)?;
+        let num_cols = stmt.execute(flags::DPI_MODE_EXEC_DESCRIBE_ONLY)?;
+        let mut column_types = Vec::with_capacity(num_cols as usize);
+
+        for pos in 1..=num_cols {
+            let info = stmt.get_query_info(pos)?;
+            let type_info = info.type_info();
+            let column_type = match type_info.oracle_type_num() {
+                ODPIOracleTypeNum::Varchar
+                | ODPIOracleTypeNum::Char
+                | ODPIOracleTypeNum::LongVarchar => {
+                    ColumnType::Text(SqlType::Varchar(type_info.size_in_chars().max(1)))
+                }
+                ODPIOracleTypeNum::NVarchar | ODPIOracleTypeNum::NChar => {
+                    ColumnType::Text(SqlType::NVarchar(type_info.size_in_chars().max(1)))
+                }
+                ODPIOracleTypeNum::Clob => ColumnType::Text(SqlType::Varchar(MAX_VARCHAR_SIZE)),
+                ODPIOracleTypeNum::NClob => ColumnType::Text(SqlType::NVarchar(MAX_VARCHAR_SIZE)),
+                ODPIOracleTypeNum::Number
+                    if type_info.scale() == 0
+                        && type_info.precision() > 0
+                        && type_info.precision() <= MAX_INT64_PRECISION =>
+                {
+                    ColumnType::Integer
+                }
+                ODPIOracleTypeNum::Number
+                    if type_info.precision() == 0
+                        || type_info.precision() > MAX_INT64_PRECISION =>
+                {
+                    ColumnType::Decimal
+                }
+                ODPIOracleTypeNum::NativeInt | ODPIOracleTypeNum::NativeUint => ColumnType::Integer,
+                ODPIOracleTypeNum::Number
+                | ODPIOracleTypeNum::NativeFloat
+                | ODPIOracleTypeNum::NativeDouble => ColumnType::Double,
+                ODPIOracleTypeNum::Date => ColumnType::Timestamp(SqlType::Date),
+                ODPIOracleTypeNum::Timestamp
+                | ODPIOracleTypeNum::TimestampTz
+                | ODPIOracleTypeNum::TimestampLtz => ColumnType::Timestamp(SqlType::Timestamp),
+                ODPIOracleTypeNum::Raw | ODPIOracleTypeNum::LongRaw => {
+                    ColumnType::Raw(type_info.db_size_in_bytes().max(1))
+                }
+                other => {
+                    return Err(ErrorKind::FromSql(
+                        "CSV".to_string(),
+                        format!("a {} column ({})", other, info.name()),
+                    )
+                    .into())
+                }
+            };
+            column_types.push(column_type);
+        }
+
+        stmt.close(None)?;
+        Ok(column_types)
+    }
+
+    /// Convert the fields of a record to the types of their columns.
+    fn convert(
+        &self,
+        fields: &[Field],
+        column_types: &[ColumnType],
+    ) -> ::std::result::Result<Vec<Option<Converted>>, String> {
+        if fields.len() != column_types.len() {
+            return Err(format!(
+                "expected {} fields, found {}",
+                column_types.len(),
+                fields.len()
+            ));
+        }
+
+        fields
+            .iter()
+            .zip(column_types)
+            .enumerate()
+            .map(|(idx, (field, column_type))| {
+                if !field.quoted && field.text == *self.options.null_marker() {
+                    return Ok(None);
+                }
+                column_type
+                    .convert(&field.text, &self.options)
+                    .map(Some)
+                    .map_err(|reason| format!("field {}: {}", idx + 1, reason))
+            })
+            .collect()
+    }
+}
+
+/// How the fields of a column are converted.
+enum ColumnType {
+    /// Character data, bound with the given type.
+    Text(SqlType),
+    /// Integers.
+    Integer,
+    /// Numbers with a precision that may not fit in a `Double`, bound as text.
+    Decimal,
+    /// Other numbers.
+    Double,
+    /// Dates and timestamps, bound with the given type.
+    Timestamp(SqlType),
+    /// Hexadecimal RAW data, of the given size in bytes.
+    Raw(u32),
+}
+
+impl ColumnType {
+    /// Get the type the column is bound with.
+    fn sql_type(&self) -> SqlType {
+        match *self {
+            ColumnType::Text(sql_type) | ColumnType::Timestamp(sql_type) => sql_type,
+            ColumnType::Integer => SqlType::Int64,
+            ColumnType::Decimal => SqlType::Varchar(NUMBER_TEXT_SIZE),
+            ColumnType::Double => SqlType::Double,
+            ColumnType::Raw(size) => SqlType::Raw(size),
+        }
+    }
+
+    /// Convert a field to the type of the column.
+    fn convert(
+        &self,
+        text: &str,
+        options: &CsvOptions,
+    ) -> ::std::result::Result<Converted, String> {
+        match *self {
+            ColumnType::Text(_) => Ok(Converted::Text(text.to_string())),
+            ColumnType::Integer => text
+                .trim()
+                .parse()
+                .map(Converted::Integer)
+                .map_err(|_| format!("'{}' is not an integer", text)),
+            ColumnType::Decimal => text
+                .trim()
+                .parse::<f64>()
+                .map(|_| Converted::Text(text.trim().to_string()))
+                .map_err(|_| format!("'{}' is not a number", text)),
+            ColumnType::Double => text
+                .trim()
+                .parse()
+                .map(Converted::Double)
+                .map_err(|_| format!("'{}' is not a number", text)),
+            ColumnType::Timestamp(_) => {
+                let format = options.date_format();
+                NaiveDateTime::parse_from_str(text.trim(), format)
+                    .or_else(|_| {
+                        NaiveDate::parse_from_str(text.trim(), format)
+                            .map(|date| date.and_hms(0, 0, 0))
+                    })
+                    .map(|timestamp| Converted::Timestamp(DateTime::from_utc(timestamp, Utc)))
+                    .map_err(|_| format!("'{}' does not match the format '{}'", text, format))
+            }
+            ColumnType::Raw(_) => from_hex(text.trim())
+                .map(Converted::Raw)
+                .ok_or_else(|| format!("'{}' is not hexadecimal", text)),
+        }
+    }
+}
+
+/// Decode hexadecimal into bytes.
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 || !text.is_ascii() {
+        return None;
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).ok())
+        .collect()
+}
+
+/// A field converted to the type of its column.
+enum Converted {
+    /// Character data.
+    Text(String),
+    /// An integer.
+    Integer(i64),
+    /// A floating point number.
+    Double(f64),
+    /// A date and time.
+    Timestamp(DateTime<Utc>),
+    /// Binary data.
+    Raw(Vec<u8>),
+}
+
+impl ToSql for Converted {
+    fn sql_type(&self) -> SqlType {
+        match *self {
+            Converted::Text(ref v) => v.sql_type(),
+            Converted::Integer(ref v) => v.sql_type(),
+            Converted::Double(ref v) => v.sql_type(),
+            Converted::Timestamp(ref v) => v.sql_type(),
+            Converted::Raw(ref v) => v.sql_type(),
+        }
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        match *self {
+            Converted::Text(ref v) => v.to_sql(var, pos, sql_type),
+            Converted::Integer(ref v) => v.to_sql(var, pos, sql_type),
+            Converted::Double(ref v) => v.to_sql(var, pos, sql_type),
+            Converted::Timestamp(ref v) => v.to_sql(var, pos, sql_type),
+            Converted::Raw(ref v) => v.to_sql(var, pos, sql_type),
+        }
+    }
+}
+
+/// A field of a CSV record.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Field {
+    /// The text of the field, without quotes.
+    text: String,
+    /// Was the field quoted?
+    quoted: bool,
+}
+
+/// Reads the records of CSV, keeping track of line numbers.
+struct Records<'a, R: Read> {
+    /// The CSV being read.
+    reader: BufReader<R>,
+    /// The options used to read the CSV.
+    options: &'a CsvOptions,
+    /// The number of lines read.
+    line: u64,
+}
+
+impl<'a, R: Read> Records<'a, R> {
+    /// Create a new `Records` reading the given CSV.
+    fn new(reader: R, options: &'a CsvOptions) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            options,
+            line: 0,
+        }
+    }
+
+    /// Read the next record, skipping blank lines. Returns the line the record starts on, with
+    /// either its fields or the reason it could not be parsed, or None at the end of the CSV.
+    #[cfg_attr(feature = "cargo-clippy", allow(type_complexity))]
+    fn next_record(&mut self) -> Result<Option<(u64, ::std::result::Result<Vec<Field>, String>)>> {
+        let mut text = String::new();
+        loop {
+            text.clear();
+            if self.reader.read_line(&mut text)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            if !text
+                .trim_right_matches(|c| c == '\r' || c == '\n')
+                .is_empty()
+            {
+                break;
+            }
+        }
+
+        let start = self.line;
+        let mut fields = Vec::new();
+        let mut field = Field {
+            text: String::new(),
+            quoted: false,
+        };
+        let mut in_quotes = false;
+
+        loop {
+            let mut chars = text.chars().peekable();
+            while let Some(c) = chars.next() {
+                if in_quotes {
+                    if c != *self.options.quote() {
+                        field.text.push(c);
+                    } else if chars.peek() == Some(self.options.quote()) {
+                        field.text.push(c);
+                        chars.next();
+                    } else {
+                        in_quotes = false;
+                    }
+                } else if c == *self.options.delimiter() {
+                    fields.push(field);
+                    field = Field {
+                        text: String::new(),
+                        quoted: false,
+                    };
+                } else if c == *self.options.quote() && field.text.is_empty() && !field.quoted {
+                    in_quotes = true;
+                    field.quoted = true;
+                } else if c != '\r' && c != '\n' {
+                    field.text.push(c);
+                }
+            }
+
+            if !in_quotes {
+                break;
+            }
+
+            text.clear();
+            if self.reader.read_line(&mut text)? == 0 {
+                return Ok(Some((start, Err("unterminated quoted field".to_string()))));
+            }
+            self.line += 1;
+        }
+        fields.push(field);
+
+        Ok(Some((start, Ok(fields))))
+    }
+}
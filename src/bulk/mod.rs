@@ -0,0 +1,192 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Bulk inserts buffer rows in one variable per column and execute the statement with array DML
+//! (`Statement::execute_batch()`) each time the buffers are full. Batch errors and array DML row
+//! counts are enabled, so a row that fails does not stop the rest of the batch, and each row added
+//! gets an outcome reporting either the number of rows it affected or the error it raised.
+use common::error;
+use connection::Connection;
+use error::{ErrorKind, Result};
+use statement::batch::MAX_BATCH_ROWS;
+use statement::Statement;
+use types::{SqlType, ToSql};
+use util::PrivateTryFromUsize;
+use variable::Var;
+
+pub mod loader;
+
+/// The number of rows buffered before the statement is executed, if no other size is given.
+const DEFAULT_ARRAY_SIZE: u32 = 100;
+
+/// The outcome of a row added to a `BulkInsert`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RowOutcome {
+    /// The row was executed successfully, affecting the given number of rows.
+    Success(u64),
+    /// The row raised the given error.
+    Failed(error::Info),
+}
+
+impl RowOutcome {
+    /// Returns true if the row was executed successfully.
+    pub fn is_success(&self) -> bool {
+        match *self {
+            RowOutcome::Success(_) => true,
+            RowOutcome::Failed(_) => false,
+        }
+    }
+}
+
+/// Inserts (or otherwise executes a DML statement for) many rows using array DML.
+pub struct BulkInsert<'conn> {
+    /// The connection the statement was prepared on.
+    conn: &'conn Connection,
+    /// The prepared statement.
+    stmt: Statement,
+    /// The type of each column.
+    column_types: Vec<SqlType>,
+    /// The variable bound to each column.
+    vars: Vec<Var>,
+    /// The number of rows buffered before the statement is executed.
+    array_size: u32,
+    /// The number of rows currently buffered.
+    buffered: u32,
+    /// The outcome of each row executed since the last call to `flush()`.
+    outcomes: Vec<RowOutcome>,
+}
+
+impl<'conn> BulkInsert<'conn> {
+    /// Prepare a statement for bulk execution, buffering up to 100 rows at a time.
+    ///
+    /// * `conn` - the connection to execute the statement on.
+    /// * `sql` - the SQL to execute, with one placeholder per column. Placeholders are bound by
+    /// position.
+    /// * `column_types` - the type of each column.
+    pub fn new(conn: &'conn Connection, sql: &str, column_types: &[SqlType]) -> Result<Self> {
+        Self::with_array_size(conn, sql, column_types, DEFAULT_ARRAY_SIZE)
+    }
+
+    /// Prepare a statement for bulk execution, buffering up to `array_size` rows at a time.
+    ///
+    /// * `conn` - the connection to execute the statement on.
+    /// * `sql` - the SQL to execute, with one placeholder per column. Placeholders are bound by
+    /// position.
+    /// * `column_types` - the type of each column.
+    /// * `array_size` - the number of rows buffered before the statement is executed, at most
+    /// 65536.
+    pub fn with_array_size(
+        conn: &'conn Connection,
+        sql: &str,
+        column_types: &[SqlType],
+        array_size: u32,
+    ) -> Result<Self> {
+        if array_size > MAX_BATCH_ROWS {
+            return Err(ErrorKind::ArraySize(array_size).into());
+        }
+
+        let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+        let array_size = array_size.max(1);
+        let mut vars = Vec::with_capacity(column_types.len());
+
+        for (idx, sql_type) in column_types.iter().enumerate() {
+            let var = conn.new_var(
+                sql_type.oracle_type_num(),
+                sql_type.native_type_num(),
+                array_size,
+                sql_type.size(),
+                sql_type.size_is_bytes(),
+                false,
+            )?;
+            stmt.bind_by_pos(u32::private_try_from(idx + 1)?, &var)?;
+            vars.push(var);
+        }
+
+        Ok(Self {
+            conn,
+            stmt,
+            column_types: column_types.to_vec(),
+            vars,
+            array_size,
+            buffered: 0,
+            outcomes: Vec::new(),
+        })
+    }
+
+    /// Get the connection the statement was prepared on.
+    pub fn connection(&self) -> &Connection {
+        self.conn
+    }
+
+    /// Get the number of rows buffered before the statement is executed.
+    pub fn array_size(&self) -> u32 {
+        self.array_size
+    }
+
+    /// Get the number of rows buffered and not yet executed.
+    pub fn pending(&self) -> u32 {
+        self.buffered
+    }
+
+    /// Add a row, executing the statement if the buffers are full. Returns the index of the row in
+    /// the outcomes returned by the next call to `flush()`. If executing the statement fails, the
+    /// buffered rows are kept and executed again when the next row is added or on `flush()`.
+    ///
+    /// * `row` - one value per column.
+    pub fn add_row(&mut self, row: &[&dyn ToSql]) -> Result<usize> {
+        if row.len() != self.column_types.len() {
+            return Err(ErrorKind::ColumnCount(self.column_types.len(), row.len()).into());
+        }
+
+        if self.buffered == self.array_size {
+            self.execute()?;
+        }
+
+        for ((value, var), sql_type) in row.iter().zip(&self.vars).zip(&self.column_types) {
+            value.to_sql(var, self.buffered, sql_type)?;
+        }
+
+        let idx = self.outcomes.len() + self.buffered as usize;
+        self.buffered += 1;
+
+        if self.buffered == self.array_size {
+            self.execute()?;
+        }
+
+        Ok(idx)
+    }
+
+    /// Execute any buffered rows and return the outcome of every row added since the last call to
+    /// `flush()`, in the order the rows were added.
+    pub fn flush(&mut self) -> Result<Vec<RowOutcome>> {
+        self.execute()?;
+        Ok(self.outcomes.drain(..).collect())
+    }
+
+    /// Execute the buffered rows, recording the outcome of each.
+    fn execute(&mut self) -> Result<()> {
+        if self.buffered == 0 {
+            return Ok(());
+        }
+
+        let batch = self.stmt.execute_batch(self.buffered)?;
+        self.buffered = 0;
+
+        let mut outcomes = batch
+            .row_counts()
+            .iter()
+            .map(|count| RowOutcome::Success(*count))
+            .collect::<Vec<RowOutcome>>();
+        for &(row, ref info) in batch.failures() {
+            outcomes[row] = RowOutcome::Failed(info.clone());
+        }
+
+        self.outcomes.extend(outcomes);
+        Ok(())
+    }
+}
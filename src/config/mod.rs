@@ -0,0 +1,277 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Connection profiles are named sets of credentials and connection settings loaded from a TOML
+//! file, with one table per profile.
+//!
+//! ```toml
+//! [orders]
+//! user = "orders"
+//! password_file = "/run/secrets/orders-password"
+//! connect_string = "//db.example.com:1521/ORCL"
+//! encoding = "UTF-8"
+//! min_sessions = 1
+//! max_sessions = 10
+//! session_increment = 1
+//! stmt_cache_size = 50
+//! ```
+//!
+//! Any value can be overridden with an environment variable named `MIMIR_<PROFILE>_<KEY>`, e.g.
+//! `MIMIR_ORDERS_PASSWORD` or `MIMIR_ORDERS_MAX_SESSIONS`. A profile that is not in the file can be
+//! defined entirely through the environment.
+use connection::builder::ConnectionBuilder;
+use connection::Connection;
+use context::Context;
+use error::{ErrorKind, Result};
+use pool::Pool;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use toml;
+
+/// A set of named connection profiles.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    /// The profiles, keyed by name.
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// Load the profiles from the TOML file at the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut contents = String::new();
+        File::open(path)?.read_to_string(&mut contents)?;
+        Self::from_toml(&contents)
+    }
+
+    /// Load the profiles from a TOML string.
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        let profiles = toml::from_str(contents)?;
+        Ok(Self { profiles })
+    }
+
+    /// Get the names of the profiles defined in the file.
+    pub fn names(&self) -> Vec<&str> {
+        self.profiles.keys().map(|name| name.as_str()).collect()
+    }
+
+    /// Get the profile with the given name, with any environment overrides applied.
+    ///
+    /// * `name` - the name of the profile.
+    pub fn profile(&self, name: &str) -> Result<Profile> {
+        let mut profile = self.profiles.get(name).cloned().unwrap_or_default();
+        let overridden = profile.apply_env(name)?;
+
+        if self.profiles.contains_key(name) || overridden {
+            Ok(profile)
+        } else {
+            Err(ErrorKind::Profile(name.to_string()).into())
+        }
+    }
+}
+
+/// A named set of credentials and connection settings. The password is left out of the `Debug`
+/// output.
+#[derive(Clone, Default, Deserialize, Getters)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// The name of the user used for authenticating.
+    #[get = "pub"]
+    user: Option<String>,
+    /// The password used for authenticating. Takes precedence over `password_file`.
+    password: Option<String>,
+    /// A file containing the password used for authenticating.
+    #[get = "pub"]
+    password_file: Option<PathBuf>,
+    /// The connect string identifying the database.
+    #[get = "pub"]
+    connect_string: Option<String>,
+    /// The encoding to use for CHAR data.
+    #[get = "pub"]
+    encoding: Option<String>,
+    /// The encoding to use for NCHAR data.
+    #[get = "pub"]
+    nchar_encoding: Option<String>,
+    /// The minimum number of sessions in a pool created from the profile.
+    #[get = "pub"]
+    min_sessions: Option<u32>,
+    /// The maximum number of sessions in a pool created from the profile.
+    #[get = "pub"]
+    max_sessions: Option<u32>,
+    /// The number of sessions a pool created from the profile grows by.
+    #[get = "pub"]
+    session_increment: Option<u32>,
+    /// The size of the statement cache, in number of statements.
+    #[get = "pub"]
+    stmt_cache_size: Option<u32>,
+}
+
+impl fmt::Debug for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Profile")
+            .field("user", &self.user)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("password_file", &self.password_file)
+            .field("connect_string", &self.connect_string)
+            .field("encoding", &self.encoding)
+            .field("nchar_encoding", &self.nchar_encoding)
+            .field("min_sessions", &self.min_sessions)
+            .field("max_sessions", &self.max_sessions)
+            .field("session_increment", &self.session_increment)
+            .field("stmt_cache_size", &self.stmt_cache_size)
+            .finish()
+    }
+}
+
+impl Profile {
+    /// Get the password, reading it from `password_file` if it is not set directly. Trailing
+    /// newlines are removed from the contents of the file.
+    pub fn password(&self) -> Result<Option<String>> {
+        if let Some(ref password) = self.password {
+            Ok(Some(password.clone()))
+        } else if let Some(ref path) = self.password_file {
+            let mut password = String::new();
+            File::open(path)?.read_to_string(&mut password)?;
+            let len = password
+                .trim_right_matches(|c| c == '\r' || c == '\n')
+                .len();
+            password.truncate(len);
+            Ok(Some(password))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Create a standalone connection using the profile.
+    ///
+    /// * `context` - the context handle created earlier using the function `Context::create()`.
+    pub fn connect(&self, context: &Context) -> Result<Connection> {
+        let mut builder = ConnectionBuilder::default();
+
+        if let Some(ref user) = self.user {
+            builder.user(user.as_str());
+        }
+        if let Some(password) = self.password()? {
+            builder.password(password);
+        }
+        if let Some(ref connect_string) = self.connect_string {
+            builder.connect_string(connect_string.as_str());
+        }
+        if let Some(ref encoding) = self.encoding {
+            builder.encoding(encoding.as_str());
+        }
+        if let Some(ref nchar_encoding) = self.nchar_encoding {
+            builder.nchar_encoding(nchar_encoding.as_str());
+        }
+
+        let conn = builder.connect(context)?;
+
+        if let Some(stmt_cache_size) = self.stmt_cache_size {
+            conn.set_statement_cache_size(stmt_cache_size)?;
+        }
+
+        Ok(conn)
+    }
+
+    /// Create a session pool using the profile.
+    ///
+    /// * `context` - the context handle created earlier using the function `Context::create()`.
+    pub fn create_pool(&self, context: &Context) -> Result<Pool> {
+        let password = self.password()?;
+        let mut comm_cp = context.init_common_create_params()?;
+        let mut pool_cp = context.init_pool_create_params()?;
+
+        if let Some(ref encoding) = self.encoding {
+            comm_cp.set_encoding(encoding)?;
+        }
+        if let Some(ref nchar_encoding) = self.nchar_encoding {
+            comm_cp.set_nchar_encoding(nchar_encoding)?;
+        }
+        if let Some(min_sessions) = self.min_sessions {
+            pool_cp.set_min_sessions(min_sessions);
+        }
+        if let Some(max_sessions) = self.max_sessions {
+            pool_cp.set_max_sessions(max_sessions);
+        }
+        if let Some(session_increment) = self.session_increment {
+            pool_cp.set_session_increment(session_increment);
+        }
+
+        let pool = Pool::create(
+            context,
+            self.user.as_ref().map(|s| s.as_str()),
+            password.as_ref().map(|s| s.as_str()),
+            self.connect_string.as_ref().map(|s| s.as_str()),
+            Some(comm_cp),
+            Some(pool_cp),
+        )?;
+
+        if let Some(stmt_cache_size) = self.stmt_cache_size {
+            pool.set_stmt_cache_size(stmt_cache_size)?;
+        }
+
+        Ok(pool)
+    }
+
+    /// Apply the `MIMIR_<PROFILE>_<KEY>` environment overrides for the named profile. Returns true
+    /// if any override was found.
+    fn apply_env(&mut self, name: &str) -> Result<bool> {
+        let prefix = format!(
+            "MIMIR_{}_",
+            name.to_uppercase()
+                .replace(|c: char| !c.is_alphanumeric(), "_")
+        );
+        let var = |key: &str| env::var(format!("{}{}", prefix, key)).ok();
+        let mut found = false;
+
+        // A password file from the environment replaces a password from the file, unless the
+        // password itself is also overridden below.
+        if let Some(value) = var("PASSWORD_FILE") {
+            self.password = None;
+            self.password_file = Some(PathBuf::from(value));
+            found = true;
+        }
+
+        {
+            let mut set_string = |key: &str, field: &mut Option<String>| {
+                if let Some(value) = var(key) {
+                    *field = Some(value);
+                    found = true;
+                }
+            };
+            set_string("USER", &mut self.user);
+            set_string("PASSWORD", &mut self.password);
+            set_string("CONNECT_STRING", &mut self.connect_string);
+            set_string("ENCODING", &mut self.encoding);
+            set_string("NCHAR_ENCODING", &mut self.nchar_encoding);
+        }
+
+        let mut numbers: [(&str, &mut Option<u32>); 4] = [
+            ("MIN_SESSIONS", &mut self.min_sessions),
+            ("MAX_SESSIONS", &mut self.max_sessions),
+            ("SESSION_INCREMENT", &mut self.session_increment),
+            ("STMT_CACHE_SIZE", &mut self.stmt_cache_size),
+        ];
+
+        for (key, field) in numbers.iter_mut() {
+            if let Some(value) = var(key) {
+                let parsed = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| ErrorKind::EnvValue(format!("{}{}", prefix, key)))?;
+                **field = Some(parsed);
+                found = true;
+            }
+        }
+
+        Ok(found)
+    }
+}
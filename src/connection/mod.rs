@@ -11,6 +11,9 @@
 //! calling the function `Pool::acquireConnection()`. They can be closed by calling the function
 //! `close()` or releasing the last reference to the connection by calling the function `release()`.
 //! Connection handles are used to create all handles other than session pools and context handles.
+#[cfg(feature = "arrow")]
+use arrow::RecordBatches;
+use auth::Auth;
 use common::{encoding, version};
 use context::params::{CommonCreate, ConnCreate, SubscrCreate};
 use context::Context;
@@ -24,16 +27,27 @@ use objecttype::ObjectType;
 use odpi::opaque::ODPIConn;
 use odpi::structs::{ODPIEncodingInfo, ODPIVersionInfo};
 use odpi::{enums, externs, flags};
+use plsql::{self, Arg, Outputs};
+use row::scrollable::ScrollableRows;
+use row::{Row, Rows};
 use slog::Logger;
+use statement::cache::{CacheStats, CachedStatement, Lru};
+use statement::returning;
 use statement::Statement;
+use std::cell::{Cell, RefCell};
 use std::convert::TryFrom;
 use std::ffi::{CStr, CString};
 use std::ptr;
 use subscription::Subscription;
+use transaction::Transaction;
+use types::{FromSql, SqlType, ToSql, Value};
 use util::ODPIStr;
 use util::PrivateTryFromUsize;
+use variable::builder::VarBuilder;
 use variable::Var;
 
+pub mod builder;
+
 /// Connection handles are used to represent connections to the database.
 #[allow(dead_code)]
 pub struct Connection {
@@ -43,6 +57,10 @@ pub struct Connection {
     stdout: Option<Logger>,
     /// Optoinal stderr logger.
     stderr: Option<Logger>,
+    /// Has execution on the connection been interrupted with `break_execution()`?
+    broken: Cell<bool>,
+    /// The statements believed to be held in the statement cache, used by `prepare_cached()`.
+    stmt_cache: RefCell<Lru>,
 }
 
 impl Connection {
@@ -79,9 +97,18 @@ impl Connection {
         }
     }
 
+    /// Begins a local transaction on the connection. Enqueue and dequeue operations performed
+    /// through the returned `Transaction` are committed or rolled back together with any DML
+    /// executed on this connection before the transaction is finished.
+    pub fn begin_transaction(&self) -> Transaction {
+        Transaction::new(self)
+    }
+
     /// Performs an immediate (asynchronous) termination of any currently executing function on the
     /// server associated with the connection.
     pub fn break_execution(&self) -> Result<()> {
+        self.broken.set(true);
+
         try_dpi!(
             externs::dpiConn_breakExecution(self.inner),
             Ok(()),
@@ -89,6 +116,28 @@ impl Connection {
         )
     }
 
+    /// Calls a stored function and returns its result, converted to `T`. IN OUT arguments are
+    /// updated with the values returned by the call.
+    ///
+    /// * `name` - the name of the function, optionally qualified by schema and package (i.e.
+    /// `pkg.func`).
+    /// * `args` - the arguments, bound by position.
+    pub fn call_func<T: FromSql + ToSql>(&self, name: &str, args: &mut [Arg]) -> Result<T> {
+        let (value, _) = plsql::call(self, name, args, Some(T::default_sql_type()))?;
+        T::from_sql(value.unwrap_or(Value::Null))
+    }
+
+    /// Calls a stored procedure and returns the values of its OUT and IN OUT arguments. IN OUT
+    /// arguments are also updated in place.
+    ///
+    /// * `name` - the name of the procedure, optionally qualified by schema and package (i.e.
+    /// `pkg.proc`).
+    /// * `args` - the arguments, bound by position.
+    pub fn call_proc(&self, name: &str, args: &mut [Arg]) -> Result<Outputs> {
+        let (_, outputs) = plsql::call(self, name, args, None)?;
+        Ok(outputs)
+    }
+
     /// Changes the password of the specified user.
     ///
     /// * `username` - the name of the user whose password is to be changed, as a byte string in the
@@ -215,6 +264,51 @@ impl Connection {
         )
     }
 
+    /// Creates a standalone connection to a database using the given authentication method. The
+    /// authentication method is validated before any call is made to the database, and the
+    /// username, password, external authentication and authorization mode are derived from it.
+    ///
+    /// * `context` - the context handle created earlier using the function `Context::create()`.
+    /// * `auth` - the authentication method.
+    /// * `connect_string` - the connect string identifying the database to which a connection is to
+    /// be established, as a byte string in the encoding used for CHAR data. None is also acceptable
+    /// for local connections (identified by the environment variable $ORACLE_SID).
+    /// * `common_create_params` - a `CommonCreate` structure which is used to specify context
+    /// parameters for connection creation. None is also acceptable in which case all default
+    /// parameters will be used when creating the connection.
+    /// * `conn_create_params` - a `ConnCreate` structure which is used to specify parameters for
+    /// connection creation. None is also acceptable in which case all default parameters will be
+    /// used when creating the connection.
+    pub fn create_with_auth(
+        context: &Context,
+        auth: &Auth,
+        connect_string: Option<&str>,
+        common_create_params: Option<CommonCreate>,
+        conn_create_params: Option<ConnCreate>,
+    ) -> Result<Self> {
+        auth.validate()?;
+
+        let mut conn_cp = if let Some(conn_create_params) = conn_create_params {
+            conn_create_params
+        } else {
+            context.init_conn_create_params()?
+        };
+        let auth_mode = conn_cp.get_auth_mode() | auth.auth_mode();
+        conn_cp.set_auth_mode(auth_mode);
+        conn_cp.set_external_auth(if auth.external_auth() { 1 } else { 0 });
+
+        let username = auth.username();
+
+        Self::create(
+            context,
+            username.as_ref().map(|s| s.as_str()),
+            auth.password(),
+            connect_string,
+            common_create_params,
+            Some(conn_cp),
+        )
+    }
+
     /// Dequeues a message from a queue.
     ///
     /// * `queue_name` - the name of the queue from which the message is to be dequeued, as a byte
@@ -223,14 +317,18 @@ impl Connection {
     /// message from the queue.
     /// * `props` -- a reference to the message properties that will be populated with information
     /// from the message that is dequeued.
+    /// * `payload` - the object the payload of the message is read into, created from the payload
+    /// type of the queue with `ObjectType::create()`.
+    ///
+    /// Returns the id of the message that was dequeued.
     pub fn deque_object(
         &self,
         queue_name: &str,
         options: &dequeue::Options,
         props: &Properties,
-    ) -> Result<(String, Object)> {
+        payload: &Object,
+    ) -> Result<String> {
         let queue_s: ODPIStr = TryFrom::try_from(queue_name)?;
-        let payload = ptr::null_mut();
         let mut pdst = ptr::null();
         let mut dstlen = 0;
 
@@ -241,11 +339,11 @@ impl Connection {
                 queue_s.len(),
                 options.inner(),
                 props.inner(),
-                payload,
+                payload.inner(),
                 &mut pdst,
                 &mut dstlen
             ),
-            Ok((ODPIStr::new(pdst, dstlen).into(), payload.into())),
+            Ok(ODPIStr::new(pdst, dstlen).into()),
             ErrorKind::Connection("dpiConn_deqObject".to_string())
         )
     }
@@ -258,13 +356,16 @@ impl Connection {
     /// message to the queue.
     /// * `props` - a reference to the message properties that will affect the message that is
     /// enqueued.
+    /// * `payload` - the payload of the message, an object of the payload type of the queue.
+    ///
+    /// Returns the id of the message that was enqueued.
     pub fn enqueue_object(
         &self,
         queue_name: &str,
         options: &enqueue::Options,
         props: &Properties,
-    ) -> Result<(String, Object)> {
-        let payload = ptr::null_mut();
+        payload: &Object,
+    ) -> Result<String> {
         let queue_s: ODPIStr = TryFrom::try_from(queue_name)?;
         let mut pdst = ptr::null();
         let mut dstlen = 0;
@@ -276,15 +377,51 @@ impl Connection {
                 queue_s.len(),
                 options.inner(),
                 props.inner(),
-                payload,
+                payload.inner(),
                 &mut pdst,
                 &mut dstlen
             ),
-            Ok((ODPIStr::new(pdst, dstlen).into(), payload.into())),
+            Ok(ODPIStr::new(pdst, dstlen).into()),
             ErrorKind::Connection("dpiConn_enqObject".to_string())
         )
     }
 
+    /// Executes a DML returning statement once for each set of parameters (array DML) and returns
+    /// the rows returned by each iteration, in order.
+    ///
+    /// * `sql` - the DML returning statement, i.e. `insert into t values (:1) returning id into
+    /// :2`. The parameters are bound by position to the placeholders before the `INTO` clause.
+    /// * `params` - the parameters of each iteration. Every iteration must have the same number of
+    /// parameters.
+    /// * `returning` - the type of each placeholder of the `INTO` clause. These placeholders cannot
+    /// be described, so the types must be given. See `statement::returning` for how they are used.
+    pub fn execute_many_returning(
+        &self,
+        sql: &str,
+        params: &[&[&dyn ToSql]],
+        returning: &[SqlType],
+    ) -> Result<Vec<Vec<Row>>> {
+        returning::execute(self, sql, params, returning)
+    }
+
+    /// Executes a DML returning statement and returns the rows it returned, one for each row
+    /// affected by the statement.
+    ///
+    /// * `sql` - the DML returning statement, i.e. `insert into t values (:1) returning id into
+    /// :2`. The parameters are bound by position to the placeholders before the `INTO` clause.
+    /// * `params` - the parameters of the statement.
+    /// * `returning` - the type of each placeholder of the `INTO` clause. These placeholders cannot
+    /// be described, so the types must be given. See `statement::returning` for how they are used.
+    pub fn execute_returning(
+        &self,
+        sql: &str,
+        params: &[&dyn ToSql],
+        returning: &[SqlType],
+    ) -> Result<Vec<Row>> {
+        let mut rows = returning::execute(self, sql, &[params], returning)?;
+        Ok(rows.pop().unwrap_or_default())
+    }
+
     /// Get the current schema.
     pub fn get_current_schema(&self) -> Result<String> {
         let mut pdst = ptr::null();
@@ -458,6 +595,12 @@ impl Connection {
         )
     }
 
+    /// Returns true if execution on the connection has been interrupted by calling the function
+    /// `break_execution()`. The state of such a connection is unknown, so it should not be reused.
+    pub fn is_broken(&self) -> bool {
+        self.broken.get()
+    }
+
     /// Returns a reference to a new set of dequeue options, used in dequeuing objects from a queue.
     /// The reference should be released as soon as it is no longer needed.
     pub fn new_deq_options(&self) -> Result<dequeue::Options> {
@@ -618,6 +761,29 @@ impl Connection {
         )
     }
 
+    /// Returns a statement prepared for execution, using the SQL text as the key for the statement
+    /// in the statement cache. The statement is returned to the cache when the `CachedStatement` is
+    /// dropped, and cache hits, misses and evictions are counted and available from the function
+    /// `statement_cache_stats()`.
+    ///
+    /// * `sql` - the SQL that is to be prepared for execution, as a string in the encoding used for
+    ///  CHAR data.
+    pub fn prepare_cached(&self, sql: &str) -> Result<CachedStatement> {
+        if self.stmt_cache.borrow().capacity().is_none() {
+            let size = self.get_statement_cache_size()?;
+            self.stmt_cache.borrow_mut().set_capacity(size);
+        }
+        self.stmt_cache.borrow_mut().lookup(sql);
+
+        match self.prepare_stmt(Some(sql), Some(sql), false) {
+            Ok(stmt) => Ok(CachedStatement::new(self, sql, stmt)),
+            Err(e) => {
+                self.statement_cache_discard(sql);
+                Err(e)
+            }
+        }
+    }
+
     /// Returns a reference to a statement prepared for execution. The reference should be released
     /// as soon as it is no longer needed.
     ///
@@ -638,7 +804,7 @@ impl Connection {
     ) -> Result<Statement> {
         let sql_s: ODPIStr = TryFrom::try_from(sql)?;
         let tag_s: ODPIStr = TryFrom::try_from(tag)?;
-        let scroll_i = if scrollable { 0 } else { 1 };
+        let scroll_i = if scrollable { 1 } else { 0 };
         let mut stmt_ptr = ptr::null_mut();
 
         try_dpi!(
@@ -656,6 +822,33 @@ impl Connection {
         )
     }
 
+    /// Executes a query and returns its rows, which are fetched as they are iterated.
+    ///
+    /// * `sql` - the query to execute.
+    /// * `params` - the parameters of the query, bound by position.
+    pub fn query(&self, sql: &str, params: &[&dyn ToSql]) -> Result<Rows> {
+        Rows::new(self.execute_query(sql, params, false)?)
+    }
+
+    /// Executes a query and returns its rows as Arrow `RecordBatch`es, each holding up to the fetch
+    /// array size of the statement. This is only available when the `arrow` feature is enabled.
+    ///
+    /// * `sql` - the query to execute.
+    /// * `params` - the parameters of the query, bound by position.
+    #[cfg(feature = "arrow")]
+    pub fn query_arrow(&self, sql: &str, params: &[&dyn ToSql]) -> Result<RecordBatches> {
+        RecordBatches::new(self, self.execute_query(sql, params, false)?)
+    }
+
+    /// Executes a query as a scrollable cursor and returns its rows, which can be fetched in any
+    /// order.
+    ///
+    /// * `sql` - the query to execute.
+    /// * `params` - the parameters of the query, bound by position.
+    pub fn query_scrollable(&self, sql: &str, params: &[&dyn ToSql]) -> Result<ScrollableRows> {
+        ScrollableRows::new(self.execute_query(sql, params, true)?)
+    }
+
     /// Rolls back the current active transaction.
     pub fn rollback(&self) -> Result<()> {
         try_dpi!(
@@ -819,7 +1012,10 @@ impl Connection {
     pub fn set_statement_cache_size(&self, size: u32) -> Result<()> {
         try_dpi!(
             externs::dpiConn_setStmtCacheSize(self.inner, size),
-            Ok(()),
+            {
+                self.stmt_cache.borrow_mut().set_capacity(size);
+                Ok(())
+            },
             ErrorKind::Connection("dpiConn_setStmtCacheSize".to_string())
         )
     }
@@ -849,6 +1045,39 @@ impl Connection {
             ErrorKind::Connection("dpiConn_startupDatabase".to_string())
         )
     }
+
+    /// Forget a statement that is no longer held in the statement cache.
+    #[doc(hidden)]
+    pub fn statement_cache_discard(&self, sql: &str) {
+        self.stmt_cache.borrow_mut().discard(sql);
+    }
+
+    /// Get the hit, miss and eviction counters of the statement cache, as seen by
+    /// `prepare_cached()`.
+    pub fn statement_cache_stats(&self) -> CacheStats {
+        self.stmt_cache.borrow().stats()
+    }
+
+    /// Prepare a query, bind its parameters by position and execute it.
+    fn execute_query(
+        &self,
+        sql: &str,
+        params: &[&dyn ToSql],
+        scrollable: bool,
+    ) -> Result<Statement> {
+        let stmt = self.prepare_stmt(Some(sql), None, scrollable)?;
+
+        for (idx, param) in params.iter().enumerate() {
+            let var = VarBuilder::default()
+                .sql_type(param.sql_type())
+                .create(self)?;
+            var.set(0, *param)?;
+            stmt.bind_by_pos(u32::private_try_from(idx + 1)?, &var)?;
+        }
+
+        stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+        Ok(stmt)
+    }
 }
 
 impl From<*mut ODPIConn> for Connection {
@@ -857,6 +1086,8 @@ impl From<*mut ODPIConn> for Connection {
             inner,
             stdout: None,
             stderr: None,
+            broken: Cell::new(false),
+            stmt_cache: RefCell::new(Default::default()),
         }
     }
 }
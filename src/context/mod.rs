@@ -25,6 +25,7 @@ use std::ptr::{self, Unique};
 use util::ODPIStr;
 
 pub mod params;
+pub mod sharding;
 
 use self::params::{CommonCreate, ConnCreate, PoolCreate, SubscrCreate};
 
@@ -8,6 +8,7 @@
 
 //! These structs are used for initializing parameters used during connection creation, pool
 //! creation, or subscription creation.
+use context::sharding::ShardingKey;
 use error::Result;
 use odpi::structs::{
     ODPIAppContext, ODPICommonCreateParams, ODPIConnCreateParams, ODPIPoolCreateParams,
@@ -217,13 +218,21 @@ impl From<ODPICommonCreateParams> for CommonCreate {
 pub struct ConnCreate {
     /// The ODPI-C dpiConnCreateParams struct.
     conn: ODPIConnCreateParams,
+    /// The sharding key, kept alive for as long as the parameters are.
+    sharding_key: Option<ShardingKey>,
+    /// The super sharding key, kept alive for as long as the parameters are.
+    super_sharding_key: Option<ShardingKey>,
 }
 
 impl ConnCreate {
     /// Create a new `ConnCreate` struct.
     #[doc(hidden)]
     pub fn new(conn: ODPIConnCreateParams) -> Self {
-        Self { conn }
+        Self {
+            conn,
+            sharding_key: None,
+            super_sharding_key: None,
+        }
     }
 
     /// Get the inner FFI struct.
@@ -232,6 +241,12 @@ impl ConnCreate {
         self.conn
     }
 
+    /// Get a mutable reference to the inner FFI struct, so ODPI-C can fill in the output members.
+    #[doc(hidden)]
+    pub fn inner_mut(&mut self) -> &mut ODPIConnCreateParams {
+        &mut self.conn
+    }
+
     /// Get the `auth_mode` value.
     ///
     /// Specifies the mode used for authorizing connections. It is expected to be one or more of the
@@ -447,6 +462,43 @@ impl ConnCreate {
     pub fn get_out_tag_found(&self) -> bool {
         self.conn.out_tag_found != 0
     }
+
+    /// Get the `sharding_key` value.
+    ///
+    /// Specifies the sharding key used to route the connection to the shard holding the data for
+    /// the key. The default value is None, which means the connection is not routed by sharding key.
+    pub fn get_sharding_key(&self) -> Option<&ShardingKey> {
+        self.sharding_key.as_ref()
+    }
+
+    /// Set the `sharding_key` value. The key is owned by the parameters, so the values in it remain
+    /// valid until the connection has been created or acquired.
+    pub fn set_sharding_key(&mut self, mut sharding_key: ShardingKey) -> Result<&mut Self> {
+        self.conn.num_sharding_key_columns = sharding_key.num_columns()?;
+        self.conn.sharding_key_columns = sharding_key.columns_ptr();
+        self.sharding_key = Some(sharding_key);
+        Ok(self)
+    }
+
+    /// Get the `super_sharding_key` value.
+    ///
+    /// Specifies the super sharding key used, together with the sharding key, to route the
+    /// connection to the shard holding the data for the key. The default value is None.
+    pub fn get_super_sharding_key(&self) -> Option<&ShardingKey> {
+        self.super_sharding_key.as_ref()
+    }
+
+    /// Set the `super_sharding_key` value. The key is owned by the parameters, so the values in it
+    /// remain valid until the connection has been created or acquired.
+    pub fn set_super_sharding_key(
+        &mut self,
+        mut super_sharding_key: ShardingKey,
+    ) -> Result<&mut Self> {
+        self.conn.num_super_sharding_key_columns = super_sharding_key.num_columns()?;
+        self.conn.super_sharding_key_columns = super_sharding_key.columns_ptr();
+        self.super_sharding_key = Some(super_sharding_key);
+        Ok(self)
+    }
 }
 
 /// This structure is used for creating session pools, which can in turn be used to create
@@ -0,0 +1,143 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Sharding keys are used to route connections to the shard holding the data for a given key in a
+//! sharded database. They are set on a `ConnCreate` with `set_sharding_key()` or
+//! `set_super_sharding_key()`, which takes ownership of the key so that the buffers backing its
+//! values stay alive for as long as the connection parameters do.
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use error::Result;
+use odpi::enums::ODPINativeTypeNum;
+use odpi::enums::ODPIOracleTypeNum;
+use odpi::structs::{ODPIBytes, ODPIDataBuffer, ODPIShardingKeyColumn, ODPITimestamp};
+use std::convert::TryFrom;
+use std::fmt;
+use std::os::raw::c_char;
+use std::ptr;
+use util::PrivateTryFromUsize;
+
+/// A sharding key (or super sharding key) made up of one or more typed column values.
+#[derive(Default)]
+pub struct ShardingKey {
+    /// The ODPI-C sharding key columns.
+    columns: Vec<ODPIShardingKeyColumn>,
+    /// The buffers backing the varchar and raw column values.
+    buffers: Vec<Vec<u8>>,
+}
+
+impl ShardingKey {
+    /// Create a new, empty `ShardingKey`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Get a pointer to the sharding key columns.
+    #[doc(hidden)]
+    pub fn columns_ptr(&mut self) -> *mut ODPIShardingKeyColumn {
+        if self.columns.is_empty() {
+            ptr::null_mut()
+        } else {
+            self.columns.as_mut_ptr()
+        }
+    }
+
+    /// Get the number of columns in the sharding key.
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns true if the sharding key has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Get the number of columns in the sharding key, as expected by ODPI-C.
+    #[doc(hidden)]
+    pub fn num_columns(&self) -> Result<u8> {
+        u8::private_try_from(self.columns.len())
+    }
+
+    /// Add a DATE column value to the sharding key.
+    pub fn date(&mut self, value: DateTime<Utc>) -> Result<&mut Self> {
+        let mut timestamp: ODPITimestamp = Default::default();
+        timestamp.year = TryFrom::try_from(value.year())?;
+        timestamp.month = TryFrom::try_from(value.month())?;
+        timestamp.day = TryFrom::try_from(value.day())?;
+        timestamp.hour = TryFrom::try_from(value.hour())?;
+        timestamp.minute = TryFrom::try_from(value.minute())?;
+        timestamp.second = TryFrom::try_from(value.second())?;
+
+        let buffer = ODPIDataBuffer {
+            as_timestamp: timestamp,
+        };
+        self.push(
+            ODPIOracleTypeNum::Date,
+            ODPINativeTypeNum::Timestamp,
+            buffer,
+        )
+    }
+
+    /// Add a NUMBER column value to the sharding key.
+    pub fn number(&mut self, value: i64) -> Result<&mut Self> {
+        let buffer = ODPIDataBuffer { as_int_64: value };
+        self.push(ODPIOracleTypeNum::Number, ODPINativeTypeNum::Int64, buffer)
+    }
+
+    /// Add a RAW column value to the sharding key.
+    pub fn raw(&mut self, value: &[u8]) -> Result<&mut Self> {
+        self.push_bytes(ODPIOracleTypeNum::Raw, value.to_vec())
+    }
+
+    /// Add a VARCHAR2 column value to the sharding key, as a string in the encoding used for CHAR
+    /// data.
+    pub fn varchar(&mut self, value: &str) -> Result<&mut Self> {
+        self.push_bytes(ODPIOracleTypeNum::Varchar, value.as_bytes().to_vec())
+    }
+
+    /// Add a column value backed by the given bytes. The bytes are kept alongside the columns, so
+    /// the pointer stored in the column stays valid when the key is moved.
+    fn push_bytes(&mut self, oracle_type: ODPIOracleTypeNum, bytes: Vec<u8>) -> Result<&mut Self> {
+        let length = u32::private_try_from(bytes.len())?;
+        let buffer = ODPIDataBuffer {
+            as_bytes: ODPIBytes {
+                ptr: bytes.as_ptr() as *mut c_char,
+                length,
+                encoding: ptr::null(),
+            },
+        };
+        self.buffers.push(bytes);
+        self.push(oracle_type, ODPINativeTypeNum::Bytes, buffer)
+    }
+
+    /// Add a column to the sharding key.
+    fn push(
+        &mut self,
+        oracle_type_num: ODPIOracleTypeNum,
+        native_type_num: ODPINativeTypeNum,
+        value: ODPIDataBuffer,
+    ) -> Result<&mut Self> {
+        u8::private_try_from(self.columns.len() + 1)?;
+        self.columns.push(ODPIShardingKeyColumn {
+            oracle_type_num,
+            native_type_num,
+            value,
+        });
+        Ok(self)
+    }
+}
+
+impl fmt::Debug for ShardingKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let types = self
+            .columns
+            .iter()
+            .map(|col| col.oracle_type_num)
+            .collect::<Vec<ODPIOracleTypeNum>>();
+        write!(f, "ShardingKey {{ columns: {:?} }}", types)
+    }
+}
@@ -23,7 +23,7 @@ use std::slice;
 use util::ODPIStr;
 
 /// This structure is used for holding Oracle year to month interval data information.
-#[derive(Default, Getters, Setters)]
+#[derive(Clone, Copy, Debug, Default, Eq, Getters, PartialEq, Setters)]
 pub struct YearsMonths {
     /// The years in an Oracle YEARS TO MONTHS interval.
     #[get = "pub"]
@@ -350,6 +350,15 @@ impl TypeInfo {
         self.inner.scale
     }
 
+    /// Get the `fs_precision` value.
+    ///
+    /// Specifies the fractional seconds precision of the column that is being queried. This value
+    /// is only populated for timestamp and interval day to second columns. For all other columns
+    /// the value is zero.
+    pub fn fs_precision(&self) -> u8 {
+        self.inner.fs_precision
+    }
+
     /// Get the `object_type` value.
     ///
     /// Specifies a reference to the type of the object that is being queried. This value is only
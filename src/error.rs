@@ -15,9 +15,31 @@ error_chain! {
         Nul(::std::ffi::NulError);
         EnvVar(::std::env::VarError);
         TryFromInt(::std::num::TryFromIntError);
+        ParseInt(::std::num::ParseIntError);
+        Toml(::toml::de::Error);
     }
 
     errors {
+        Argument(idx: usize) {
+            description("No OUT or IN OUT value for the argument!")
+            display("No OUT or IN OUT value for argument {}!", idx)
+        }
+        Arrow(msg: String) {
+            description("Unable to build the Arrow record batch!")
+            display("Unable to build the Arrow record batch: {}!", msg)
+        }
+        ArraySize(size: u32) {
+            description("The array size exceeds the limit of 65536 rows!")
+            display("Array size {} exceeds the limit of 65536 rows!", size)
+        }
+        Auth(reason: String) {
+            description("Invalid authentication!")
+            display("Invalid authentication: {}!", reason)
+        }
+        BindField(name: String) {
+            description("No field for the bind variable!")
+            display("No field for bind variable '{}'!", name)
+        }
         BranchId {
             description("The given batch id is longer than 64 bytes!")
             display("The given batch id is longer than 64 bytes!")
@@ -30,6 +52,14 @@ error_chain! {
             description("Context: call to ODPI-C function failed!")
             display("Context: call to '{}' function failed!", fn_name)
         }
+        ColumnCount(expected: usize, found: usize) {
+            description("The number of values does not match the number of columns!")
+            display("Expected {} values, found {}!", expected, found)
+        }
+        ColumnIndex(idx: usize) {
+            description("No column at the given index!")
+            display("No column at index {}!", idx)
+        }
         ContextCreateFailed {
             description("Failed to create the ODPI-C context!")
             display("Failed to create the ODPI-C context!")
@@ -42,6 +72,10 @@ error_chain! {
             description("DeqOptions: call to ODPI-C function failed!")
             display("DeqOptions: call to '{}' function failed!", fn_name)
         }
+        Deserialize(msg: String) {
+            description("Unable to deserialize the row!")
+            display("Unable to deserialize the row: {}!", msg)
+        }
         DpiError(err: Info) {
             description("ODPI-C Error")
             display("ODPI-C Error! {}", err)
@@ -50,6 +84,18 @@ error_chain! {
             description("EnqOptions: call to ODPI-C function failed!")
             display("EnqOptions: call to '{}' function failed!", fn_name)
         }
+        EnvValue(name: String) {
+            description("Invalid value in an environment variable!")
+            display("Invalid value in environment variable '{}'!", name)
+        }
+        FromSql(value: String, target: String) {
+            description("Unable to convert the value to the requested type!")
+            display("Unable to convert a {} value to {}!", value, target)
+        }
+        Identifier(name: String) {
+            description("Invalid identifier!")
+            display("Invalid identifier '{}'!", name)
+        }
         Length {
             description("Unable to calculate the length of the data")
             display("Unable to calculate the length of the data")
@@ -58,6 +104,22 @@ error_chain! {
             description("LOB: call to ODPI-C function failed!")
             display("LOB: call to '{}' function failed!", fn_name)
         }
+        Migration(version: u64, statement: usize) {
+            description("A migration failed!")
+            display("Migration {} failed at statement {}!", version, statement)
+        }
+        MigrationChecksum(version: u64) {
+            description("An applied migration has changed!")
+            display("Migration {} has changed since it was applied!", version)
+        }
+        MigrationLock(name: String, status: i64) {
+            description("Unable to take the migration lock!")
+            display("Unable to take the migration lock '{}' (status {})!", name, status)
+        }
+        MigrationName(name: String) {
+            description("Invalid or duplicate migration file name!")
+            display("Invalid or duplicate migration file name '{}'!", name)
+        }
         MsgProps(fn_name: String) {
             description("MsgProps: call to ODPI-C function failed!")
             display("MsgProps: call to '{}' function failed!", fn_name)
@@ -78,14 +140,30 @@ error_chain! {
             description("OCI Error!")
             display("OCI Error! {}", err)
         }
+        PlsqlName(name: String) {
+            description("Invalid PL/SQL procedure or function name!")
+            display("Invalid PL/SQL procedure or function name '{}'!", name)
+        }
         Pool(fn_name: String) {
             description("Pool: call to ODPI-C function failed!")
             display("Pool: call to '{}' function failed!", fn_name)
         }
+        Profile(name: String) {
+            description("Connection profile not found!")
+            display("Connection profile '{}' not found!", name)
+        }
         Rowid(fn_name: String) {
             description("Rowid: call to ODPI-C function failed!")
             display("Rowid: call to '{}' function failed!", fn_name)
         }
+        Serialize(msg: String) {
+            description("Unable to serialize the value!")
+            display("Unable to serialize the value: {}!", msg)
+        }
+        SqlType(value: String, sql_type: String) {
+            description("Unable to write the value into a variable of the given type!")
+            display("Unable to write a {} value into a variable of type {}!", value, sql_type)
+        }
         Statement(fn_name: String) {
             description("Statement: call to ODPI-C function failed!")
             display("Statement: call to '{}' function failed!", fn_name)
@@ -98,9 +176,48 @@ error_chain! {
             description("The given transaction id is longer than 64 bytes!")
             display("The given transaction id is longer than 64 bytes!")
         }
+        UnboundField(name: String) {
+            description("No bind variable for the field!")
+            display("No bind variable for field '{}'!", name)
+        }
+        UntypedVar {
+            description("The variable was not created with a SqlType!")
+            display("The variable was not created with a SqlType!")
+        }
+        Url(url: String) {
+            description("Unable to parse the connection URL!")
+            display("Unable to parse the connection URL '{}'!", url)
+        }
         Var(fn_name: String) {
             description("Var: call to ODPI-C function failed!")
             display("Var: call to '{}' function failed!", fn_name)
         }
+        Visibility {
+            description("Queue operations in a transaction must use OnCommit visibility!")
+            display("Queue operations in a transaction must use OnCommit visibility!")
+        }
+    }
+}
+
+impl Error {
+    /// Returns true if the error was raised because a call to an ODPI-C function failed, in which
+    /// case the database error is available from `Context::get_error()`.
+    pub fn is_odpi_failure(&self) -> bool {
+        match *self.kind() {
+            ErrorKind::Connection(_)
+            | ErrorKind::Context(_)
+            | ErrorKind::DeqOptions(_)
+            | ErrorKind::EnqOptions(_)
+            | ErrorKind::Lob(_)
+            | ErrorKind::MsgProps(_)
+            | ErrorKind::Object(_)
+            | ErrorKind::ObjectType(_)
+            | ErrorKind::Pool(_)
+            | ErrorKind::Rowid(_)
+            | ErrorKind::Statement(_)
+            | ErrorKind::Subscription(_)
+            | ErrorKind::Var(_) => true,
+            _ => false,
+        }
     }
 }
@@ -8,8 +8,8 @@
 
 //! Rust bindings over the Oracle Database Programming Interface for Drivers and Applications.
 #![deny(missing_docs)]
-#![feature(ptr_internals, try_from, untagged_unions)]
-#![recursion_limit = "128"]
+#![feature(ptr_internals)]
+#![recursion_limit = "256"]
 
 #[macro_use]
 extern crate bitflags;
@@ -20,6 +20,8 @@ extern crate error_chain;
 #[macro_use]
 extern crate getset;
 #[macro_use]
+extern crate serde_derive;
+#[macro_use]
 mod macros;
 #[cfg(feature = "trace")]
 #[macro_use]
@@ -28,12 +30,24 @@ extern crate slog;
 #[macro_use]
 extern crate slog_try;
 
+#[cfg(feature = "arrow")]
+extern crate arrow as arrow_crate;
 extern crate chrono;
 extern crate hex_slice;
+#[cfg(feature = "r2d2")]
+extern crate r2d2 as r2d2_crate;
+#[macro_use]
+extern crate serde as serde_crate;
 #[cfg(not(feature = "trace"))]
 extern crate slog;
+extern crate toml;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+mod auth;
+mod bulk;
 mod common;
+pub mod config;
 mod connection;
 mod context;
 mod data;
@@ -42,24 +56,38 @@ mod enqueue;
 mod error;
 mod lob;
 mod message;
+pub mod migrate;
 mod object;
 mod objectattr;
 mod objecttype;
 mod odpi;
+mod plsql;
 mod pool;
 mod query;
+#[cfg(feature = "r2d2")]
+pub mod r2d2;
+mod row;
 mod rowid;
+pub mod serde;
 mod statement;
 mod subscription;
+mod transaction;
+mod types;
 mod util;
 mod variable;
 
 // Public API
 
+pub use auth::Auth;
+pub use bulk::loader::{BulkLoader, LoadSummary, RejectedLine};
+pub use bulk::{BulkInsert, RowOutcome};
+pub use common::error::Info as ErrorInfo;
+pub use connection::builder::{ConnectOptions, ConnectionBuilder};
 pub use connection::Connection;
 pub use context::params::AppContext;
+pub use context::sharding::ShardingKey;
 pub use context::Context;
-pub use data::{Data, TypeInfo};
+pub use data::{Data, TypeInfo, YearsMonths};
 pub use dequeue::Options as DeqOptions;
 pub use enqueue::Options as EnqOptions;
 pub use error::{Error, Result};
@@ -69,12 +97,26 @@ pub use object::Object;
 pub use objectattr::ObjectAttr;
 pub use objecttype::ObjectType;
 pub use odpi::structs::{
-    ODPIBytes, ODPIData, ODPIDataBuffer, ODPIObjectAttrInfo, ODPIObjectTypeInfo, ODPISubscrMessage,
+    ODPIBytes, ODPIData, ODPIDataBuffer, ODPIDataTypeInfo, ODPIObjectAttrInfo, ODPIObjectTypeInfo,
+    ODPISubscrMessage,
 };
 pub use odpi::{constants, enums, flags};
+pub use plsql::{Arg, InOutParam, Out, Outputs};
+pub use pool::pooled::PooledConnection;
+pub use pool::stats::{Histogram, PoolStats, WAIT_THRESHOLD};
 pub use pool::Pool;
+pub use query::column::Column;
 pub use query::Info as QueryInfo;
+pub use row::csv::{CsvBuilder, CsvOptions};
+pub use row::json::{JsonBuilder, JsonOptions};
+pub use row::scrollable::ScrollableRows;
+pub use row::{Row, Rows};
 pub use rowid::Rowid;
+pub use statement::batch::BatchResult;
+pub use statement::cache::{CacheStats, CachedStatement};
 pub use statement::Statement;
-pub use util::ODPIStr;
+pub use transaction::Transaction;
+pub use types::{FromSql, SqlType, ToSql, Value};
+pub use util::{check_identifier, ODPIStr};
+pub use variable::builder::{VarBuilder, VarOptions};
 pub use variable::Var;
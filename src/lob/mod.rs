@@ -169,12 +169,17 @@ impl Lob {
     }
 
     /// Reads data from the LOB at the specified offset into the provided buffer.
+    ///
+    /// * `offset` - the offset into the LOB data from which to start reading. The first position
+    /// is 1.
+    /// * `length` - the amount of data to read, in characters for character LOBs and in bytes for
+    /// binary LOBs. Character data is returned in the encoding of the connection, so the buffer is
+    /// sized with `Lob::get_buffer_size()`.
     #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
     pub fn read_bytes(&self, offset: u64, length: u64) -> Result<Vec<i8>> {
-        let length_usize: usize = length as usize;
-        let mut buffer: Vec<i8> = Vec::with_capacity(length_usize);
+        let mut buf_len = self.get_buffer_size(length)?;
+        let mut buffer: Vec<i8> = Vec::with_capacity(buf_len as usize);
         let buf_ptr = buffer.as_mut_ptr();
-        let mut buf_len = length;
 
         try_dpi!(
             externs::dpiLob_readBytes(self.inner, offset, length, buf_ptr, &mut buf_len),
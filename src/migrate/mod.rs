@@ -0,0 +1,409 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Schema migrations are versioned `.sql` scripts applied in order by a `Migrator`. Scripts are
+//! named `V<version>__<description>.sql`, i.e. `V3__add_orders_index.sql`, and are split into
+//! statements with `split()`.
+//!
+//! Each applied migration is recorded in a history table, created on first use, along with a
+//! checksum of its script. A migration that is already recorded is skipped, unless its script
+//! has changed since, which is an error. A `DBMS_LOCK` lock is held while migrating, so deploys
+//! running at the same time apply each migration once. Migrating stops at the first statement
+//! that fails, and the migrations applied before it stay applied.
+//!
+//! DDL commits implicitly in Oracle, so a migration that fails part way through may leave the
+//! changes made by its earlier statements behind. Keeping one DDL statement per migration avoids
+//! this.
+use connection::Connection;
+use error::{Error, ErrorKind, Result};
+use odpi::flags;
+use plsql::{Arg, Out};
+use statement::Statement;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::time::Instant;
+use types::ToSql;
+use util::{check_identifier, crc32, PrivateTryFromUsize};
+use variable::builder::VarBuilder;
+
+mod split;
+
+pub use self::split::{is_plsql, split};
+
+/// The history table used if no other is given.
+const DEFAULT_TABLE: &str = "MIMIR_SCHEMA_HISTORY";
+
+/// The name of the lock held while migrating if no other is given.
+const DEFAULT_LOCK_NAME: &str = "MIMIR_MIGRATE";
+
+/// The number of seconds to wait for the lock if no other time is given.
+const DEFAULT_LOCK_TIMEOUT: u32 = 60;
+
+/// The `DBMS_LOCK.X_MODE` lock mode.
+const EXCLUSIVE_MODE: i64 = 6;
+
+/// The `DBMS_LOCK.REQUEST` status for a lock that was taken.
+const LOCK_TAKEN: i64 = 0;
+
+/// The `DBMS_LOCK.REQUEST` status for a lock that is already held by the session.
+const LOCK_HELD: i64 = 4;
+
+/// The options used when migrating. These are usually built with a `MigrateBuilder`.
+#[derive(Builder, Clone, Debug, Getters)]
+#[builder(name = "MigrateBuilder", setter(into))]
+pub struct MigrateOptions {
+    /// The name of the history table.
+    #[builder(default = "DEFAULT_TABLE.to_string()")]
+    #[get = "pub"]
+    table: String,
+    /// The name of the `DBMS_LOCK` lock held while migrating.
+    #[builder(default = "DEFAULT_LOCK_NAME.to_string()")]
+    #[get = "pub"]
+    lock_name: String,
+    /// The number of seconds to wait for the lock.
+    #[builder(default = "DEFAULT_LOCK_TIMEOUT")]
+    #[get = "pub"]
+    lock_timeout: u32,
+}
+
+impl Default for MigrateOptions {
+    fn default() -> Self {
+        Self {
+            table: DEFAULT_TABLE.to_string(),
+            lock_name: DEFAULT_LOCK_NAME.to_string(),
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+        }
+    }
+}
+
+/// A versioned migration script.
+#[derive(Clone, Debug, Eq, Getters, PartialEq)]
+pub struct Migration {
+    /// The version of the migration.
+    #[get = "pub"]
+    version: u64,
+    /// The description of the migration, from its file name.
+    #[get = "pub"]
+    description: String,
+    /// The name of the script file.
+    #[get = "pub"]
+    script: String,
+    /// The SQL of the script.
+    #[get = "pub"]
+    sql: String,
+    /// The CRC-32 checksum of the script, ignoring the difference between `\r\n` and `\n` line
+    /// endings.
+    #[get = "pub"]
+    checksum: u32,
+}
+
+impl Migration {
+    /// Create a new migration.
+    ///
+    /// * `version` - the version of the migration.
+    /// * `description` - what the migration does.
+    /// * `script` - the name of the script, recorded in the history table.
+    /// * `sql` - the SQL of the script.
+    pub fn new(version: u64, description: &str, script: &str, sql: &str) -> Self {
+        let sql = sql.replace("\r\n", "\n");
+
+        Self {
+            version,
+            description: description.to_string(),
+            script: script.to_string(),
+            checksum: crc32(sql.as_bytes()),
+            sql,
+        }
+    }
+
+    /// Read a migration from a file named `V<version>__<description>.sql`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let script = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let (version, description) =
+            parse_name(&script).ok_or_else(|| ErrorKind::MigrationName(script.clone()))?;
+
+        let mut sql = String::new();
+        File::open(path)?.read_to_string(&mut sql)?;
+        Ok(Self::new(version, &description, &script, &sql))
+    }
+}
+
+/// Read the migrations in a directory, sorted by version. Files without the `.sql` extension are
+/// ignored.
+pub fn load<P: AsRef<Path>>(dir: P) -> Result<Vec<Migration>> {
+    let mut migrations = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_sql = path
+            .extension()
+            .map_or(false, |ext| ext.eq_ignore_ascii_case("sql"));
+        if path.is_file() && is_sql {
+            migrations.push(Migration::from_file(&path)?);
+        }
+    }
+
+    sort(&mut migrations)?;
+    Ok(migrations)
+}
+
+/// Sort migrations by version, making sure no two have the same version.
+fn sort(migrations: &mut Vec<Migration>) -> Result<()> {
+    migrations.sort_by_key(|migration| migration.version);
+
+    for pair in migrations.windows(2) {
+        if pair[0].version == pair[1].version {
+            return Err(ErrorKind::MigrationName(pair[1].script.clone()).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Split a file name of the form `V<version>__<description>.sql` into the version and the
+/// description, with underscores replaced by spaces.
+fn parse_name(name: &str) -> Option<(u64, String)> {
+    if !name.to_ascii_lowercase().ends_with(".sql") {
+        return None;
+    }
+    let stem = &name[..name.len() - 4];
+    if !stem.starts_with('V') && !stem.starts_with('v') {
+        return None;
+    }
+
+    let idx = stem.find("__")?;
+    let version = stem[1..idx].parse().ok()?;
+    let description = stem[idx + 2..].replace('_', " ");
+    Some((version, description))
+}
+
+/// Applies migrations to a database.
+pub struct Migrator<'conn> {
+    /// The connection the migrations are applied with.
+    conn: &'conn Connection,
+    /// The options used when migrating.
+    options: MigrateOptions,
+}
+
+impl<'conn> Migrator<'conn> {
+    /// Create a new `Migrator` with the default options.
+    ///
+    /// * `conn` - the connection the migrations are applied with.
+    pub fn new(conn: &'conn Connection) -> Self {
+        Self::with_options(conn, MigrateOptions::default())
+    }
+
+    /// Create a new `Migrator` with the given options.
+    ///
+    /// * `conn` - the connection the migrations are applied with.
+    /// * `options` - the options used when migrating.
+    pub fn with_options(conn: &'conn Connection, options: MigrateOptions) -> Self {
+        Self { conn, options }
+    }
+
+    /// Get the options used when migrating.
+    pub fn options(&self) -> &MigrateOptions {
+        &self.options
+    }
+
+    /// Apply the migrations in a directory that have not been applied yet, returning the versions
+    /// applied.
+    ///
+    /// * `dir` - the directory holding the migration scripts.
+    pub fn migrate<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<u64>> {
+        self.apply(load(dir)?)
+    }
+
+    /// Apply the given migrations that have not been applied yet, in order of version, returning
+    /// the versions applied.
+    ///
+    /// The migrations are applied while holding a `DBMS_LOCK` lock. `DBMS_LOCK.ALLOCATE_UNIQUE`
+    /// commits, so any transaction open on the connection is committed before the first migration
+    /// is applied. If the session already holds the lock, it is left held afterwards.
+    ///
+    /// * `migrations` - the migrations.
+    pub fn apply(&self, mut migrations: Vec<Migration>) -> Result<Vec<u64>> {
+        check_identifier(&self.options.table)?;
+        sort(&mut migrations)?;
+
+        let handle = self.lock()?;
+        let result = self.apply_locked(&migrations);
+        let released = match handle {
+            Some(ref handle) => self.unlock(handle),
+            None => Ok(()),
+        };
+
+        let applied = result?;
+        released?;
+        Ok(applied)
+    }
+
+    /// Get the version and checksum of each applied migration.
+    pub fn applied(&self) -> Result<BTreeMap<u64, u32>> {
+        check_identifier(&self.options.table)?;
+        let mut applied = BTreeMap::new();
+        if !self.history_exists()? {
+            return Ok(applied);
+        }
+
+        let sql = format!("select version, checksum from {}", self.options.table);
+        for row in self.conn.query(&sql, &[])? {
+            let row = row?;
+            applied.insert(row.get(0)?, row.get(1)?);
+        }
+
+        Ok(applied)
+    }
+
+    /// Apply migrations while holding the lock.
+    fn apply_locked(&self, migrations: &[Migration]) -> Result<Vec<u64>> {
+        if !self.history_exists()? {
+            self.execute(
+                &format!(
+                    "create table {} (version number(19) primary key, \
+                     description varchar2(200) not null, script varchar2(1000) not null, \
+                     checksum number(10) not null, installed_on timestamp default \
+                     systimestamp not null, execution_ms number(19) not null)",
+                    self.options.table
+                ),
+                &[],
+            )?;
+        }
+
+        let applied = self.applied()?;
+        let mut versions = Vec::new();
+
+        for migration in migrations {
+            if let Some(checksum) = applied.get(&migration.version) {
+                if *checksum != migration.checksum {
+                    return Err(ErrorKind::MigrationChecksum(migration.version).into());
+                }
+                continue;
+            }
+
+            self.apply_one(migration)?;
+            versions.push(migration.version);
+        }
+
+        Ok(versions)
+    }
+
+    /// Run the statements of a migration and record it in the history table.
+    fn apply_one(&self, migration: &Migration) -> Result<()> {
+        let start = Instant::now();
+
+        for (idx, sql) in split(&migration.sql).iter().enumerate() {
+            if let Err(e) = self.execute(sql, &[]) {
+                self.conn.rollback()?;
+                return Err(Error::with_chain(
+                    e,
+                    ErrorKind::Migration(migration.version, idx + 1),
+                ));
+            }
+        }
+
+        let elapsed = start.elapsed();
+        let millis = elapsed.as_secs() * 1_000 + u64::from(elapsed.subsec_millis());
+        self.execute(
+            &format!(
+                "insert into {} (version, description, script, checksum, execution_ms) \
+                 values (:1, :2, :3, :4, :5)",
+                self.options.table
+            ),
+            &[
+                &migration.version,
+                &migration.description,
+                &migration.script,
+                &migration.checksum,
+                &millis,
+            ],
+        )?;
+        self.conn.commit()
+    }
+
+    /// Does the history table exist?
+    fn history_exists(&self) -> Result<bool> {
+        let table = self.options.table.to_uppercase();
+        let (owner, name) = match table.find('.') {
+            Some(idx) => (Some(&table[..idx]), &table[idx + 1..]),
+            None => (None, &table[..]),
+        };
+
+        let mut rows = match owner {
+            Some(owner) => self.conn.query(
+                "select count(*) from all_tables where owner = :1 and table_name = :2",
+                &[&owner, &name],
+            )?,
+            None => self.conn.query(
+                "select count(*) from user_tables where table_name = :1",
+                &[&name],
+            )?,
+        };
+
+        match rows.next() {
+            Some(row) => Ok(row?.get::<i64>(0)? > 0),
+            None => Ok(false),
+        }
+    }
+
+    /// Take the migration lock, returning its handle, or None if the session already held it and
+    /// it must not be released.
+    fn lock(&self) -> Result<Option<String>> {
+        let lock_name = self.options.lock_name.as_str();
+        let outputs = self.conn.call_proc(
+            "dbms_lock.allocate_unique",
+            &mut [Arg::In(&lock_name), Out::<String>::new()],
+        )?;
+        let handle: String = outputs.get(1)?;
+
+        let status: i64 = self.conn.call_func(
+            "dbms_lock.request",
+            &mut [
+                Arg::In(&handle),
+                Arg::In(&EXCLUSIVE_MODE),
+                Arg::In(&self.options.lock_timeout),
+            ],
+        )?;
+
+        match status {
+            LOCK_TAKEN => Ok(Some(handle)),
+            LOCK_HELD => Ok(None),
+            status => Err(ErrorKind::MigrationLock(self.options.lock_name.clone(), status).into()),
+        }
+    }
+
+    /// Release the migration lock.
+    fn unlock(&self, handle: &str) -> Result<()> {
+        let _status: i64 = self
+            .conn
+            .call_func("dbms_lock.release", &mut [Arg::In(&handle)])?;
+        Ok(())
+    }
+
+    /// Execute a statement with parameters bound by position.
+    fn execute(&self, sql: &str, params: &[&dyn ToSql]) -> Result<Statement> {
+        let stmt = self.conn.prepare_stmt(Some(sql), None, false)?;
+
+        for (idx, param) in params.iter().enumerate() {
+            let var = VarBuilder::default()
+                .sql_type(param.sql_type())
+                .create(self.conn)?;
+            var.set(0, *param)?;
+            stmt.bind_by_pos(u32::private_try_from(idx + 1)?, &var)?;
+        }
+
+        stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+        Ok(stmt)
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Scripts are split into statements the way SQL*Plus reads them. A SQL statement ends with a `;`
+//! or with a line holding only `/`. A PL/SQL block (anything starting with `BEGIN`, `DECLARE` or
+//! `CREATE` of a procedure, function, package, trigger or type) contains semicolons of its own, so
+//! it ends only with a `/` line or the end of the script. Semicolons and slashes inside string
+//! literals, quoted identifiers and comments are ignored.
+
+/// Where the scanner is within a statement.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum State {
+    /// In SQL or PL/SQL code.
+    Code,
+    /// In a `/* ... */` comment.
+    BlockComment,
+    /// In a string literal or quoted identifier ended by the given character.
+    Quoted(char),
+    /// In a `q'[...]'` string literal ended by the given character and a quote.
+    QQuoted(char),
+}
+
+/// Split a script into statements. SQL statements are returned without their terminating `;`,
+/// while PL/SQL blocks keep theirs. Comments within a statement are kept, and statements made up
+/// only of comments are dropped.
+pub fn split(script: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut has_code = false;
+    let mut state = State::Code;
+
+    for line in script.lines() {
+        if state == State::Code && line.trim() == "/" {
+            flush(&mut statements, &mut current, &mut has_code);
+            continue;
+        }
+
+        let mut chars = line.chars().peekable();
+        let mut prev = None;
+        while let Some(c) = chars.next() {
+            current.push(c);
+
+            match state {
+                State::Code => match c {
+                    '-' if chars.peek() == Some(&'-') => {
+                        current.extend(chars.by_ref());
+                    }
+                    '/' if chars.peek() == Some(&'*') => {
+                        current.extend(chars.next());
+                        state = State::BlockComment;
+                    }
+                    '\'' | '"' => {
+                        has_code = true;
+                        state = State::Quoted(c);
+                    }
+                    'q' | 'Q' if chars.peek() == Some(&'\'') && !prev.map_or(false, is_ident) => {
+                        has_code = true;
+                        current.extend(chars.next());
+                        if let Some(open) = chars.next() {
+                            current.push(open);
+                            state = State::QQuoted(closing(open));
+                        }
+                    }
+                    ';' if !is_plsql(&current) => {
+                        current.pop();
+                        flush(&mut statements, &mut current, &mut has_code);
+                    }
+                    c if !c.is_whitespace() => has_code = true,
+                    _ => {}
+                },
+                State::BlockComment => {
+                    if c == '*' && chars.peek() == Some(&'/') {
+                        current.extend(chars.next());
+                        state = State::Code;
+                    }
+                }
+                State::Quoted(quote) => {
+                    // A doubled quote closes and immediately reopens the literal.
+                    if c == quote {
+                        state = State::Code;
+                    }
+                }
+                State::QQuoted(close) => {
+                    if c == close && chars.peek() == Some(&'\'') {
+                        current.extend(chars.next());
+                        state = State::Code;
+                    }
+                }
+            }
+
+            prev = Some(c);
+        }
+
+        current.push('\n');
+    }
+
+    flush(&mut statements, &mut current, &mut has_code);
+    statements
+}
+
+/// Add the current statement, if it holds any code, and start a new one.
+fn flush(statements: &mut Vec<String>, current: &mut String, has_code: &mut bool) {
+    if *has_code {
+        statements.push(current.trim().to_string());
+    }
+    current.clear();
+    *has_code = false;
+}
+
+/// Can the character be part of an unquoted identifier?
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || c == '#'
+}
+
+/// Get the character that closes a `q'` literal opened with the given character.
+fn closing(open: char) -> char {
+    match open {
+        '[' => ']',
+        '{' => '}',
+        '(' => ')',
+        '<' => '>',
+        c => c,
+    }
+}
+
+/// Is the statement a PL/SQL block, which ends only with a `/` line? Leading comments are skipped
+/// and only the first words are looked at, which may be split over several lines.
+pub fn is_plsql(sql: &str) -> bool {
+    let mut rest = sql.trim_left();
+    loop {
+        if rest.starts_with("--") {
+            rest = rest
+                .find('\n')
+                .map_or("", |idx| &rest[idx + 1..])
+                .trim_left();
+        } else if rest.starts_with("/*") {
+            rest = rest
+                .find("*/")
+                .map_or("", |idx| &rest[idx + 2..])
+                .trim_left();
+        } else {
+            break;
+        }
+    }
+
+    let words: Vec<String> = rest
+        .split(|c: char| !is_ident(c))
+        .filter(|word| !word.is_empty())
+        .take(5)
+        .map(|word| word.to_uppercase())
+        .collect();
+
+    match words.first().map(|word| word.as_str()) {
+        Some("BEGIN") | Some("DECLARE") => true,
+        Some("CREATE") => words[1..]
+            .iter()
+            .find(|word| match word.as_str() {
+                "OR" | "REPLACE" | "EDITIONABLE" | "NONEDITIONABLE" => false,
+                _ => true,
+            })
+            .map_or(false, |word| match word.as_str() {
+                "PROCEDURE" | "FUNCTION" | "PACKAGE" | "TRIGGER" | "TYPE" => true,
+                _ => false,
+            }),
+        _ => false,
+    }
+}
@@ -83,13 +83,20 @@ pub enum ODPIFetchMode {
     /// this mode.
     Prior = 16,
     /// Scroll the cursor to the row identified by the offset parameter using absolute positioning.
-    Absolte = 32,
+    Absolute = 32,
     /// Scroll the cursor to the row identified by the offset parameter using relative positioning.
     /// A positive number will move forward in the result set while a negative number will move
     /// backwards in the result set.
     Relative = 64,
 }
 
+#[allow(non_upper_case_globals)]
+impl ODPIFetchMode {
+    /// The misspelled name `ODPIFetchMode::Absolute` was previously exported under.
+    #[deprecated(note = "use `ODPIFetchMode::Absolute` instead")]
+    pub const Absolte: ODPIFetchMode = ODPIFetchMode::Absolute;
+}
+
 #[repr(u32)]
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 /// This enumeration identifies the delivery mode used for filtering messages when dequeuing
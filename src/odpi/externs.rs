@@ -380,6 +380,7 @@ extern "C" {
 }
 
 extern "C" {
+    pub fn dpiLob_addRef(lob: *mut opaque::ODPILob) -> ::std::os::raw::c_int;
     pub fn dpiLob_closeResource(lob: *mut opaque::ODPILob) -> ::std::os::raw::c_int;
     pub fn dpiLob_copy(
         lob: *mut opaque::ODPILob,
@@ -513,6 +514,7 @@ extern "C" {
 }
 
 extern "C" {
+    pub fn dpiObject_addRef(obj: *mut opaque::ODPIObject) -> ::std::os::raw::c_int;
     pub fn dpiObject_appendElement(
         obj: *mut opaque::ODPIObject,
         nativeTypeNum: enums::ODPINativeTypeNum,
@@ -679,6 +681,7 @@ extern "C" {
 }
 
 extern "C" {
+    pub fn dpiRowid_addRef(rowid: *mut opaque::ODPIRowid) -> ::std::os::raw::c_int;
     pub fn dpiRowid_getStringValue(
         rowid: *mut opaque::ODPIRowid,
         value: *mut *const ::std::os::raw::c_char,
@@ -688,6 +691,7 @@ extern "C" {
 }
 
 extern "C" {
+    pub fn dpiStmt_addRef(stmt: *mut opaque::ODPIStmt) -> ::std::os::raw::c_int;
     pub fn dpiStmt_bindByName(
         stmt: *mut opaque::ODPIStmt,
         name: *const ::std::os::raw::c_char,
@@ -717,6 +721,11 @@ extern "C" {
         tag: *const ::std::os::raw::c_char,
         tagLength: u32,
     ) -> ::std::os::raw::c_int;
+    pub fn dpiStmt_define(
+        stmt: *mut opaque::ODPIStmt,
+        pos: u32,
+        var: *mut opaque::ODPIVar,
+    ) -> ::std::os::raw::c_int;
     pub fn dpiStmt_defineValue(
         stmt: *mut opaque::ODPIStmt,
         pos: u32,
@@ -794,6 +803,11 @@ extern "C" {
         stmt: *mut opaque::ODPIStmt,
         count: *mut u64,
     ) -> ::std::os::raw::c_int;
+    pub fn dpiStmt_getRowCounts(
+        stmt: *mut opaque::ODPIStmt,
+        numRowCounts: *mut u32,
+        rowCounts: *mut *mut u64,
+    ) -> ::std::os::raw::c_int;
     pub fn dpiStmt_release(stmt: *mut opaque::ODPIStmt) -> ::std::os::raw::c_int;
     pub fn dpiStmt_scroll(
         stmt: *mut opaque::ODPIStmt,
@@ -801,6 +815,10 @@ extern "C" {
         offset: i32,
         rowCountOffset: i32,
     ) -> ::std::os::raw::c_int;
+    pub fn dpiStmt_setFetchArraySize(
+        stmt: *mut opaque::ODPIStmt,
+        arraySize: u32,
+    ) -> ::std::os::raw::c_int;
 }
 
 extern "C" {
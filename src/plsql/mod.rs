@@ -0,0 +1,189 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Stored procedures and functions are called with `Connection::call_proc()` and
+//! `Connection::call_func()`, which generate an anonymous `begin ... end;` block with one
+//! positional placeholder per argument and bind each argument through a `Var`. IN arguments are
+//! written into their variable, OUT arguments are read back once the block has executed, and IN
+//! OUT arguments are both written and updated in place. REF CURSOR outputs are returned as
+//! `Statement`s that can be fetched from after the call.
+use connection::Connection;
+use error::{ErrorKind, Result};
+use odpi::flags;
+use std::marker::PhantomData;
+use types::{FromSql, SqlType, ToSql, Value};
+use util::{check_identifier, PrivateTryFromUsize};
+use variable::builder::VarBuilder;
+use variable::Var;
+
+/// An argument passed to a stored procedure or function.
+pub enum Arg<'a> {
+    /// An IN argument.
+    In(&'a dyn ToSql),
+    /// An OUT argument of the given type. Usually created with `Out::<T>::new()`.
+    Output(SqlType),
+    /// An IN OUT argument, which is updated with the value returned by the call.
+    InOut(&'a mut dyn InOutParam),
+}
+
+/// Creates OUT arguments of the type used for values of `T`.
+pub struct Out<T> {
+    /// The type of the OUT argument.
+    phantom: PhantomData<T>,
+}
+
+impl<T: ToSql> Out<T> {
+    /// Create an OUT argument for values of type `T`.
+    pub fn new<'a>() -> Arg<'a> {
+        Arg::Output(T::default_sql_type())
+    }
+}
+
+/// A value that can be passed as an IN OUT argument.
+pub trait InOutParam: ToSql {
+    /// Get the type of the variable used to bind the argument, which must be large enough for the
+    /// value returned by the call.
+    fn out_sql_type(&self) -> SqlType;
+
+    /// Update the argument with the value returned by the call.
+    fn set_value(&mut self, value: Value) -> Result<()>;
+}
+
+impl<T: ToSql + FromSql> InOutParam for T {
+    fn out_sql_type(&self) -> SqlType {
+        T::default_sql_type().widest(self.sql_type())
+    }
+
+    fn set_value(&mut self, value: Value) -> Result<()> {
+        *self = T::from_sql(value)?;
+        Ok(())
+    }
+}
+
+/// The values of the OUT and IN OUT arguments of a call.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outputs {
+    /// The value of each argument, by position. IN arguments have no value.
+    values: Vec<Option<Value>>,
+}
+
+impl Outputs {
+    /// Get the value of the OUT or IN OUT argument at the given position, converted to `T`.
+    ///
+    /// * `idx` - the position of the argument in the argument list, starting from 0.
+    pub fn get<T: FromSql>(&self, idx: usize) -> Result<T> {
+        match self.values.get(idx) {
+            Some(&Some(ref value)) => T::from_sql(value.clone()),
+            _ => Err(ErrorKind::Argument(idx).into()),
+        }
+    }
+
+    /// Get the number of arguments.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if there were no arguments.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// Call a stored procedure, or a stored function if `ret` is given.
+///
+/// * `conn` - the connection to call the procedure or function on.
+/// * `name` - the name of the procedure or function, optionally qualified by schema and package.
+/// * `args` - the arguments.
+/// * `ret` - the type of the value returned by the function, or None for a procedure.
+#[doc(hidden)]
+pub fn call(
+    conn: &Connection,
+    name: &str,
+    args: &mut [Arg],
+    ret: Option<SqlType>,
+) -> Result<(Option<Value>, Outputs)> {
+    let sql = call_sql(name, args.len(), ret.is_some())?;
+    let stmt = conn.prepare_stmt(Some(&sql), None, false)?;
+    let mut pos = 1;
+
+    let ret_var = match ret {
+        Some(sql_type) => {
+            let var = new_var(conn, sql_type)?;
+            stmt.bind_by_pos(pos, &var)?;
+            pos += 1;
+            Some(var)
+        }
+        None => None,
+    };
+
+    let mut vars = Vec::with_capacity(args.len());
+    for arg in args.iter() {
+        let var = match *arg {
+            Arg::In(value) => {
+                let var = new_var(conn, value.sql_type())?;
+                var.set(0, value)?;
+                var
+            }
+            Arg::Output(sql_type) => new_var(conn, sql_type)?,
+            Arg::InOut(ref value) => {
+                let var = new_var(conn, value.out_sql_type())?;
+                var.set(0, &**value)?;
+                var
+            }
+        };
+        stmt.bind_by_pos(pos, &var)?;
+        pos += 1;
+        vars.push(var);
+    }
+
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+
+    let ret_value = match ret_var {
+        Some(var) => Some(var.get::<Value>(0)?),
+        None => None,
+    };
+
+    let mut values = Vec::with_capacity(args.len());
+    for (arg, var) in args.iter_mut().zip(vars) {
+        let value = match *arg {
+            Arg::In(_) => None,
+            Arg::Output(_) => Some(var.get::<Value>(0)?),
+            Arg::InOut(ref mut param) => {
+                let value = var.get::<Value>(0)?;
+                param.set_value(value.clone())?;
+                Some(value)
+            }
+        };
+        values.push(value);
+    }
+
+    Ok((ret_value, Outputs { values }))
+}
+
+/// Create a variable holding a single value of the given type.
+fn new_var(conn: &Connection, sql_type: SqlType) -> Result<Var> {
+    VarBuilder::default().sql_type(sql_type).create(conn)
+}
+
+/// Generate the anonymous block used to call a procedure or function.
+fn call_sql(name: &str, num_args: usize, is_func: bool) -> Result<String> {
+    check_identifier(name).map_err(|_| ErrorKind::PlsqlName(name.to_string()))?;
+
+    let first = if is_func { 2 } else { 1 };
+    u32::private_try_from(num_args + first)?;
+    let placeholders = (first..num_args + first)
+        .map(|pos| format!(":{}", pos))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    if is_func {
+        Ok(format!("begin :1 := {}({}); end;", name, placeholders))
+    } else {
+        Ok(format!("begin {}({}); end;", name, placeholders))
+    }
+}
@@ -10,7 +10,9 @@
 //! and can be closed by calling the function `close()` or releasing the last reference to the pool
 //! by calling the function `release()`. Pools can be used to create connections by calling the
 //! function `acquireConnection()`.
+use auth::Auth;
 use common::encoding;
+use common::error::Info;
 use connection::Connection;
 use context::params::{CommonCreate, ConnCreate, PoolCreate};
 use context::Context;
@@ -20,13 +22,26 @@ use odpi::structs::ODPIEncodingInfo;
 use odpi::{enums, externs, flags};
 use std::convert::TryFrom;
 use std::ptr::{self, Unique};
+use std::sync::Mutex;
+use std::time::Instant;
 use util::ODPIStr;
 
+pub mod pooled;
+pub mod stats;
+
+use self::pooled::PooledConnection;
+use self::stats::{Metrics, PoolStats};
+
 /// This structure represents session pools and is available by handle to a calling application or '
 /// driver.
 pub struct Pool {
     /// An ODPI-C dpiPool opaque struct pointer.
     inner: Unique<ODPIPool>,
+    /// The checkout metrics recorded for the pool.
+    metrics: Mutex<Metrics>,
+    /// A context owned by the pool, used to read the error raised by a failed checkout. ODPI-C
+    /// keeps the last error of each thread, so it does not matter which context reads it.
+    context: Context,
 }
 
 impl Pool {
@@ -36,6 +51,48 @@ impl Pool {
         self.inner.as_ptr()
     }
 
+    /// Acquires a connection using the given connection parameters, leaving any output values
+    /// (such as the tag of the acquired session) in the parameters.
+    fn acquire(
+        &self,
+        username: Option<&str>,
+        password: Option<&str>,
+        conn_cp: &mut ConnCreate,
+    ) -> Result<Connection> {
+        let username_s: ODPIStr = TryFrom::try_from(username)?;
+        let password_s: ODPIStr = TryFrom::try_from(password)?;
+        let mut conn: *mut ODPIConn = ptr::null_mut();
+        let start = Instant::now();
+
+        let res = try_dpi!(
+            externs::dpiPool_acquireConnection(
+                self.inner.as_ptr(),
+                username_s.ptr(),
+                username_s.len(),
+                password_s.ptr(),
+                password_s.len(),
+                conn_cp.inner_mut(),
+                &mut conn
+            ),
+            Ok(conn.into()),
+            ErrorKind::Pool("dpiPool_acquireConnection".to_string())
+        );
+
+        let elapsed = start.elapsed();
+        // The error has to be read before any other ODPI-C call is made on this thread.
+        let timed_out = res.is_err() && is_timeout(&self.context.get_error());
+
+        if let Ok(mut metrics) = self.metrics.lock() {
+            if res.is_ok() {
+                metrics.checkout(elapsed);
+            } else {
+                metrics.failure(elapsed, timed_out);
+            }
+        }
+
+        res
+    }
+
     /// Acquires a connection from the pool and returns a reference to it. This reference should be
     /// released as soon as it is no longer needed.
     ///
@@ -54,28 +111,28 @@ impl Pool {
         password: Option<&str>,
         conn_create_params: Option<ConnCreate>,
     ) -> Result<Connection> {
-        let username_s: ODPIStr = TryFrom::try_from(username)?;
-        let password_s: ODPIStr = TryFrom::try_from(password)?;
-        let conn_cp = if let Some(conn_create_params) = conn_create_params {
+        let mut conn_cp = if let Some(conn_create_params) = conn_create_params {
             conn_create_params
         } else {
             Default::default()
         };
-        let mut conn: *mut ODPIConn = ptr::null_mut();
 
-        try_dpi!(
-            externs::dpiPool_acquireConnection(
-                self.inner.as_ptr(),
-                username_s.ptr(),
-                username_s.len(),
-                password_s.ptr(),
-                password_s.len(),
-                &mut conn_cp.inner(),
-                &mut conn
-            ),
-            Ok(conn.into()),
-            ErrorKind::Pool("dpiPool_acquireConnection".to_string())
-        )
+        self.acquire(username, password, &mut conn_cp)
+    }
+
+    /// Acquires a connection and pings it. If the ping fails, the session is dropped from the pool
+    /// and a second connection is acquired and checked in the same way.
+    fn checkout(&self, conn_cp: &mut ConnCreate) -> Result<Connection> {
+        let conn = self.acquire(None, None, conn_cp)?;
+
+        if conn.ping().is_ok() {
+            return Ok(conn);
+        }
+
+        let _ = conn.close(flags::DPI_MODE_CONN_CLOSE_DROP, None);
+        let conn = self.acquire(None, None, conn_cp)?;
+        conn.ping()?;
+        Ok(conn)
     }
 
     /// Closes the pool and makes it unusable for further activity.
@@ -153,6 +210,98 @@ impl Pool {
         )
     }
 
+    /// Creates a session pool using the given authentication method. The authentication method is
+    /// validated before any call is made to the database. Privileged authentication cannot be used
+    /// with session pools.
+    ///
+    /// * `context` - the context handle created earlier using the function `Context::create()`.
+    /// * `auth` - the authentication method used for the sessions in the pool.
+    /// * `connect_string` - the connect string identifying the database to which connections are to
+    /// be established by the session pool, as a string in the encoding used for CHAR data. None is
+    /// also acceptable for local connections (identified by the environment variable ORACLE_SID).
+    /// * `common_create_params` - a `CommonCreate` structure which is used to specify context
+    /// parameters for pool creation. None is also acceptable in which case all default parameters
+    /// will be used when creating the pool.
+    /// * `pool_create_params` - a `PoolCreate` structure which is used to specify parameters for
+    /// pool creation. None is also acceptable in which case all default parameters will be used for
+    /// pool creation.
+    pub fn create_with_auth(
+        context: &Context,
+        auth: &Auth,
+        connect_string: Option<&str>,
+        common_create_params: Option<CommonCreate>,
+        pool_create_params: Option<PoolCreate>,
+    ) -> Result<Self> {
+        auth.validate()?;
+
+        if let Auth::Privileged { .. } = *auth {
+            return Err(ErrorKind::Auth(
+                "privileged authentication cannot be used with session pools".to_string(),
+            )
+            .into());
+        }
+
+        let mut pool_cp = if let Some(pool_create_params) = pool_create_params {
+            pool_create_params
+        } else {
+            context.init_pool_create_params()?
+        };
+        pool_cp.set_external_auth(auth.external_auth());
+
+        let username = auth.username();
+
+        Self::create(
+            context,
+            username.as_ref().map(|s| s.as_str()),
+            auth.password(),
+            connect_string,
+            common_create_params,
+            Some(pool_cp),
+        )
+    }
+
+    /// Gets a connection from the pool, wrapped in a `PooledConnection` that is released back to
+    /// the pool when it is dropped. The session is pinged before it is handed out, and if the ping
+    /// fails the session is dropped from the pool and another one is acquired in its place.
+    pub fn get(&self) -> Result<PooledConnection> {
+        let mut conn_cp: ConnCreate = Default::default();
+        let conn = self.checkout(&mut conn_cp)?;
+        Ok(PooledConnection::new(self, conn))
+    }
+
+    /// Gets a connection from the pool whose session state is described by the given tag. Sessions
+    /// carrying the tag are preferred, but any session is accepted. When the session that is
+    /// returned does not carry the requested tag, `init` is called with the connection and the tag
+    /// the session was found with (empty if it was untagged), so that it can bring the session into
+    /// the requested state, e.g. by executing `ALTER SESSION` statements. The session is then
+    /// retagged with the requested tag when it is released back to the pool.
+    ///
+    /// If `init` fails, the session is dropped from the pool, since its state is unknown.
+    ///
+    /// * `tag` - the tag describing the required session state, e.g. "NLS=FRENCH;TZ=UTC", in the
+    /// encoding used for CHAR data.
+    /// * `init` - the function used to initialize the session state.
+    pub fn get_tagged<F>(&self, tag: &str, init: F) -> Result<PooledConnection>
+    where
+        F: FnOnce(&Connection, &str) -> Result<()>,
+    {
+        let mut conn_cp: ConnCreate = Default::default();
+        conn_cp.set_tag(tag)?.set_match_any_tag(true);
+
+        let conn = self.checkout(&mut conn_cp)?;
+        let mut pooled = PooledConnection::new(self, conn);
+
+        if !conn_cp.get_out_tag_found() {
+            if let Err(e) = init(&pooled, &conn_cp.get_out_tag()) {
+                pooled.drop_session();
+                return Err(e);
+            }
+            pooled.retag(tag);
+        }
+
+        Ok(pooled)
+    }
+
     /// Returns the number of sessions in the pool that are busy.
     pub fn get_busy_count(&self) -> Result<u32> {
         let mut busy_count = 0;
@@ -236,6 +385,26 @@ impl Pool {
         )
     }
 
+    /// Returns a snapshot of the statistics for the pool, combining the session counts and
+    /// settings reported by ODPI-C with the checkout waits, timeouts and latencies recorded each
+    /// time a connection is acquired from the pool. The snapshot can be rendered in the Prometheus
+    /// text exposition format with `PoolStats::to_prometheus()`.
+    pub fn stats(&self) -> Result<PoolStats> {
+        let metrics = match self.metrics.lock() {
+            Ok(metrics) => metrics.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+
+        Ok(PoolStats::new(
+            self.get_busy_count()?,
+            self.get_open_count()?,
+            self.get_timeout()?,
+            self.get_max_lifetime_session()?,
+            self.get_stmt_cache_size()?,
+            metrics,
+        ))
+    }
+
     /// Sets the mode used for acquiring or getting connections from the pool.
     ///
     /// * `get_mode` - A value from the `ODPIGetMode` enumeration.
@@ -287,7 +456,26 @@ impl TryFrom<*mut ODPIPool> for Pool {
 
     fn try_from(inner: *mut ODPIPool) -> Result<Self> {
         let pool = Unique::new(inner).ok_or_else(|| ErrorKind::Pool("try_from".to_string()))?;
-        Ok(Self { inner: pool })
+        let context = Context::create().map_err(|e| {
+            unsafe {
+                externs::dpiPool_release(inner);
+            }
+            e
+        })?;
+        Ok(Self {
+            inner: pool,
+            metrics: Default::default(),
+            context,
+        })
+    }
+}
+
+/// Returns true if the error was raised because no session became available in the pool, either
+/// at once (ORA-24418), within the wait timeout (ORA-24457) or within the call timeout (DPI-1067).
+fn is_timeout(err: &Info) -> bool {
+    match err.code() {
+        24_418 | 24_457 => true,
+        _ => err.message().starts_with("DPI-1067:"),
     }
 }
 
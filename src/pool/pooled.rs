@@ -0,0 +1,123 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Pooled connections are connections acquired from a session pool by calling the function
+//! `Pool::get()`. They dereference to a `Connection` and are released back to the pool when they
+//! are dropped.
+use connection::Connection;
+use error::Result;
+use odpi::flags;
+use pool::Pool;
+use std::ops::{Deref, DerefMut};
+
+/// A connection acquired from a session pool that is released back to the pool on drop.
+pub struct PooledConnection<'pool> {
+    /// The pool the connection was acquired from.
+    pool: &'pool Pool,
+    /// The acquired connection.
+    conn: Connection,
+    /// The mode used when releasing the connection back to the pool.
+    close_mode: flags::ODPIConnCloseMode,
+    /// The tag to set on the session when it is released with `DPI_MODE_CONN_CLOSE_RETAG`.
+    tag: Option<String>,
+    /// Has the connection already been released back to the pool?
+    released: bool,
+}
+
+impl<'pool> PooledConnection<'pool> {
+    /// Create a new `PooledConnection`.
+    #[doc(hidden)]
+    pub fn new(pool: &'pool Pool, conn: Connection) -> Self {
+        Self {
+            pool,
+            conn,
+            close_mode: flags::DPI_MODE_CONN_CLOSE_DEFAULT,
+            tag: None,
+            released: false,
+        }
+    }
+
+    /// Get the pool the connection was acquired from.
+    pub fn pool(&self) -> &Pool {
+        self.pool
+    }
+
+    /// Drop the session from the pool when the connection is released, instead of returning it to
+    /// the pool for future use. This is useful when the session is known to be in a bad state.
+    pub fn drop_session(&mut self) -> &mut Self {
+        self.close_mode = flags::DPI_MODE_CONN_CLOSE_DROP;
+        self.tag = None;
+        self
+    }
+
+    /// Tag the session with the given tag when the connection is released back to the pool. An
+    /// empty tag clears any tag already on the session.
+    ///
+    /// * `tag` - the tag to set on the session, in the encoding used for CHAR data.
+    pub fn retag(&mut self, tag: &str) -> &mut Self {
+        self.close_mode = flags::DPI_MODE_CONN_CLOSE_RETAG;
+        self.tag = if tag.is_empty() {
+            None
+        } else {
+            Some(tag.to_string())
+        };
+        self
+    }
+
+    /// Release the connection back to the pool now, reporting any error that occurs. Dropping the
+    /// connection has the same effect but ignores errors.
+    pub fn release(mut self) -> Result<()> {
+        self.close_conn()
+    }
+
+    /// Release the connection back to the pool now, using the given close mode and tag. This takes
+    /// the place of `Connection::close()`, so that the connection is not closed a second time when
+    /// it is dropped.
+    ///
+    /// * `mode` - one or more of the values from the enumeration `ODPIConnCloseMode`, OR'ed
+    /// together.
+    /// * `tag` - the tag to set on the session when the close mode includes the value
+    /// DPI_MODE_CONN_CLOSE_RETAG, in the encoding used for CHAR data.
+    pub fn close(&mut self, mode: flags::ODPIConnCloseMode, tag: Option<&str>) -> Result<()> {
+        if self.released {
+            return Ok(());
+        }
+        self.close_mode = mode;
+        self.tag = tag.map(|t| t.to_string());
+        self.close_conn()
+    }
+
+    /// Close the connection using the configured close mode and tag.
+    fn close_conn(&mut self) -> Result<()> {
+        self.released = true;
+        let tag = self.tag.as_ref().map(|t| t.as_str());
+        self.conn.close(self.close_mode, tag)
+    }
+}
+
+impl<'pool> Deref for PooledConnection<'pool> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl<'pool> DerefMut for PooledConnection<'pool> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+}
+
+impl<'pool> Drop for PooledConnection<'pool> {
+    fn drop(&mut self) {
+        if !self.released {
+            let _ = self.close_conn();
+        }
+    }
+}
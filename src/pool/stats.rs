@@ -0,0 +1,382 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Pool statistics are a snapshot of the state of a session pool, returned by the function
+//! `Pool::stats()`. The counts and settings come from ODPI-C, while checkout waits, timeouts and
+//! latencies are recorded by mimir each time a connection is acquired from the pool. ODPI-C does
+//! not report whether a checkout waited, so a checkout that takes longer than `WAIT_THRESHOLD` is
+//! counted as a wait.
+use std::fmt;
+use std::time::Duration;
+
+/// The upper bounds, in seconds, of the checkout latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// The checkout latency above which a checkout is counted as a wait, either for a busy session to
+/// be released or for a new session to be created.
+pub const WAIT_THRESHOLD: Duration = Duration::from_millis(10);
+
+/// A latency histogram with fixed buckets.
+#[derive(Clone, Debug, Getters)]
+pub struct Histogram {
+    /// The upper bound of each bucket, in seconds.
+    #[get = "pub"]
+    bounds: Vec<f64>,
+    /// The number of observations in each bucket. The last entry counts the observations that are
+    /// larger than the largest bound.
+    #[get = "pub"]
+    counts: Vec<u64>,
+    /// The sum of all of the observations, in seconds.
+    #[get = "pub"]
+    sum: f64,
+    /// The number of observations.
+    #[get = "pub"]
+    count: u64,
+}
+
+impl Histogram {
+    /// Record an observation.
+    fn observe(&mut self, elapsed: Duration) {
+        let secs = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) * 1e-9;
+        let idx = self
+            .bounds
+            .iter()
+            .position(|bound| secs <= *bound)
+            .unwrap_or_else(|| self.bounds.len());
+        self.counts[idx] += 1;
+        self.sum += secs;
+        self.count += 1;
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            bounds: LATENCY_BUCKETS.to_vec(),
+            counts: vec![0; LATENCY_BUCKETS.len() + 1],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+}
+
+/// The checkout metrics recorded by a pool.
+#[derive(Clone, Debug, Default)]
+#[doc(hidden)]
+pub struct Metrics {
+    /// The number of connections acquired from the pool.
+    checkouts: u64,
+    /// The number of checkouts that took longer than `WAIT_THRESHOLD`.
+    waits: u64,
+    /// The number of checkouts that failed because no session was available.
+    timeouts: u64,
+    /// The number of checkouts that failed for any other reason.
+    errors: u64,
+    /// The time taken by each checkout.
+    latency: Histogram,
+}
+
+impl Metrics {
+    /// Record a successful checkout.
+    #[doc(hidden)]
+    pub fn checkout(&mut self, elapsed: Duration) {
+        self.checkouts += 1;
+        if elapsed > WAIT_THRESHOLD {
+            self.waits += 1;
+        }
+        self.latency.observe(elapsed);
+    }
+
+    /// Record a failed checkout.
+    #[doc(hidden)]
+    pub fn failure(&mut self, elapsed: Duration, timed_out: bool) {
+        if elapsed > WAIT_THRESHOLD {
+            self.waits += 1;
+        }
+        if timed_out {
+            self.timeouts += 1;
+        } else {
+            self.errors += 1;
+        }
+    }
+}
+
+/// A snapshot of the statistics for a session pool.
+#[derive(Clone, Debug, Getters)]
+pub struct PoolStats {
+    /// The number of sessions in the pool that are busy.
+    #[get = "pub"]
+    busy_count: u32,
+    /// The number of sessions in the pool that are open.
+    #[get = "pub"]
+    open_count: u32,
+    /// The amount of time, in seconds, after which idle sessions in the pool are terminated.
+    #[get = "pub"]
+    timeout: u32,
+    /// The maximum lifetime of all sessions in the pool, in seconds.
+    #[get = "pub"]
+    max_lifetime_session: u32,
+    /// The default size of the statement cache for sessions in the pool.
+    #[get = "pub"]
+    stmt_cache_size: u32,
+    /// The number of connections acquired from the pool.
+    #[get = "pub"]
+    checkouts: u64,
+    /// The number of checkouts that took longer than `WAIT_THRESHOLD`.
+    #[get = "pub"]
+    waits: u64,
+    /// The number of checkouts that failed because no session became available, either at once
+    /// when the get mode of the pool is `NoWait` or before a timeout expired in any get mode.
+    #[get = "pub"]
+    checkout_timeouts: u64,
+    /// The number of checkouts that failed for any other reason.
+    #[get = "pub"]
+    checkout_errors: u64,
+    /// The time taken by each successful checkout.
+    #[get = "pub"]
+    checkout_latency: Histogram,
+}
+
+impl PoolStats {
+    /// Create a new `PoolStats` snapshot.
+    #[doc(hidden)]
+    pub fn new(
+        busy_count: u32,
+        open_count: u32,
+        timeout: u32,
+        max_lifetime_session: u32,
+        stmt_cache_size: u32,
+        metrics: Metrics,
+    ) -> Self {
+        Self {
+            busy_count,
+            open_count,
+            timeout,
+            max_lifetime_session,
+            stmt_cache_size,
+            checkouts: metrics.checkouts,
+            waits: metrics.waits,
+            checkout_timeouts: metrics.timeouts,
+            checkout_errors: metrics.errors,
+            checkout_latency: metrics.latency,
+        }
+    }
+
+    /// Render the statistics in the Prometheus text exposition format. The given labels are added
+    /// to every sample, which allows several pools to be exposed on the same endpoint.
+    ///
+    /// * `labels` - a slice of label name and value pairs, e.g. `&[("pool", "orders")]`.
+    pub fn to_prometheus(&self, labels: &[(&str, &str)]) -> String {
+        let base = labels
+            .iter()
+            .map(|&(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+            .collect::<Vec<String>>();
+        let plain = format_labels(&base);
+        let mut out = String::new();
+
+        let gauges = [
+            (
+                "mimir_pool_busy_sessions",
+                "The number of sessions in the pool that are busy.",
+                u64::from(self.busy_count),
+            ),
+            (
+                "mimir_pool_open_sessions",
+                "The number of sessions in the pool that are open.",
+                u64::from(self.open_count),
+            ),
+            (
+                "mimir_pool_timeout_seconds",
+                "The time after which idle sessions in the pool are terminated.",
+                u64::from(self.timeout),
+            ),
+            (
+                "mimir_pool_max_lifetime_session_seconds",
+                "The maximum lifetime of all sessions in the pool.",
+                u64::from(self.max_lifetime_session),
+            ),
+            (
+                "mimir_pool_stmt_cache_size",
+                "The default size of the statement cache for sessions in the pool.",
+                u64::from(self.stmt_cache_size),
+            ),
+        ];
+
+        for &(name, help, value) in &gauges {
+            push_metric(&mut out, name, help, "gauge");
+            out.push_str(&format!("{}{} {}\n", name, plain, value));
+        }
+
+        let counters = [
+            (
+                "mimir_pool_checkouts_total",
+                "The number of connections acquired from the pool.",
+                self.checkouts,
+            ),
+            (
+                "mimir_pool_checkout_waits_total",
+                "The number of checkouts that took longer than 10ms.",
+                self.waits,
+            ),
+            (
+                "mimir_pool_checkout_timeouts_total",
+                "The number of checkouts that failed because no session was available.",
+                self.checkout_timeouts,
+            ),
+            (
+                "mimir_pool_checkout_errors_total",
+                "The number of checkouts that failed for any other reason.",
+                self.checkout_errors,
+            ),
+        ];
+
+        for &(name, help, value) in &counters {
+            push_metric(&mut out, name, help, "counter");
+            out.push_str(&format!("{}{} {}\n", name, plain, value));
+        }
+
+        let name = "mimir_pool_checkout_duration_seconds";
+        push_metric(
+            &mut out,
+            name,
+            "The time taken to acquire a connection from the pool.",
+            "histogram",
+        );
+
+        let latency = &self.checkout_latency;
+        let mut cumulative = 0;
+        for (idx, count) in latency.counts.iter().enumerate() {
+            cumulative += count;
+            let le = match latency.bounds.get(idx) {
+                Some(bound) => format!("{}", bound),
+                None => "+Inf".to_string(),
+            };
+            let mut bucket = base.clone();
+            bucket.push(format!("le=\"{}\"", le));
+            out.push_str(&format!(
+                "{}_bucket{} {}\n",
+                name,
+                format_labels(&bucket),
+                cumulative
+            ));
+        }
+        out.push_str(&format!("{}_sum{} {}\n", name, plain, latency.sum));
+        out.push_str(&format!("{}_count{} {}\n", name, plain, latency.count));
+
+        out
+    }
+}
+
+impl fmt::Display for PoolStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_prometheus(&[]))
+    }
+}
+
+/// Write the HELP and TYPE lines for a metric.
+fn push_metric(out: &mut String, name: &str, help: &str, kind: &str) {
+    out.push_str(&format!("# HELP {} {}\n", name, help));
+    out.push_str(&format!("# TYPE {} {}\n", name, kind));
+}
+
+/// Format a set of labels, or nothing if there are none.
+fn format_labels(labels: &[String]) -> String {
+    if labels.is_empty() {
+        "".to_string()
+    } else {
+        format!("{{{}}}", labels.join(","))
+    }
+}
+
+/// Escape a label value as required by the text exposition format.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Histogram, Metrics, PoolStats, WAIT_THRESHOLD};
+    use std::time::Duration;
+
+    #[test]
+    fn histogram() {
+        let mut histogram = Histogram::default();
+        histogram.observe(Duration::from_millis(1));
+        histogram.observe(Duration::from_millis(3));
+        histogram.observe(Duration::from_millis(250));
+        histogram.observe(Duration::from_secs(60));
+
+        assert_eq!(histogram.counts().len(), histogram.bounds().len() + 1);
+        assert_eq!(histogram.counts()[0], 1);
+        assert_eq!(histogram.counts()[1], 1);
+        assert_eq!(histogram.counts()[6], 1);
+        assert_eq!(histogram.counts()[12], 1);
+        assert_eq!(*histogram.count(), 4);
+        assert!((histogram.sum() - 60.254).abs() < 1e-9);
+    }
+
+    #[test]
+    fn metrics() {
+        let mut metrics = Metrics::default();
+        metrics.checkout(Duration::from_millis(1));
+        metrics.checkout(WAIT_THRESHOLD + Duration::from_millis(5));
+        metrics.failure(WAIT_THRESHOLD * 3, true);
+        metrics.failure(Duration::from_millis(1), false);
+
+        let stats = PoolStats::new(1, 2, 0, 0, 20, metrics);
+        assert_eq!(*stats.checkouts(), 2);
+        assert_eq!(*stats.waits(), 2);
+        assert_eq!(*stats.checkout_timeouts(), 1);
+        assert_eq!(*stats.checkout_errors(), 1);
+        assert_eq!(*stats.checkout_latency().count(), 2);
+    }
+
+    #[test]
+    fn prometheus() {
+        let mut metrics = Metrics::default();
+        metrics.checkout(Duration::from_millis(1));
+        metrics.checkout(Duration::from_millis(20));
+        let stats = PoolStats::new(1, 2, 60, 0, 20, metrics);
+
+        let text = stats.to_prometheus(&[("pool", "or\"ders")]);
+        assert!(text.contains(
+            "# HELP mimir_pool_busy_sessions The number of sessions in the pool that are busy.\n"
+        ));
+        assert!(text.contains("# TYPE mimir_pool_busy_sessions gauge\n"));
+        assert!(text.contains("mimir_pool_busy_sessions{pool=\"or\\\"ders\"} 1\n"));
+        assert!(text.contains("mimir_pool_timeout_seconds{pool=\"or\\\"ders\"} 60\n"));
+        assert!(text.contains("# TYPE mimir_pool_checkouts_total counter\n"));
+        assert!(text.contains("mimir_pool_checkouts_total{pool=\"or\\\"ders\"} 2\n"));
+        assert!(text.contains("mimir_pool_checkout_waits_total{pool=\"or\\\"ders\"} 1\n"));
+        assert!(text.contains("# TYPE mimir_pool_checkout_duration_seconds histogram\n"));
+        assert!(text.contains(
+            "mimir_pool_checkout_duration_seconds_bucket{pool=\"or\\\"ders\",le=\"0.001\"} 1\n"
+        ));
+        assert!(text.contains(
+            "mimir_pool_checkout_duration_seconds_bucket{pool=\"or\\\"ders\",le=\"0.01\"} 1\n"
+        ));
+        assert!(text.contains(
+            "mimir_pool_checkout_duration_seconds_bucket{pool=\"or\\\"ders\",le=\"0.025\"} 2\n"
+        ));
+        assert!(text.contains(
+            "mimir_pool_checkout_duration_seconds_bucket{pool=\"or\\\"ders\",le=\"+Inf\"} 2\n"
+        ));
+        assert!(
+            text.contains("mimir_pool_checkout_duration_seconds_count{pool=\"or\\\"ders\"} 2\n")
+        );
+
+        let plain = stats.to_string();
+        assert!(plain.contains("mimir_pool_checkouts_total 2\n"));
+        assert!(plain.contains("mimir_pool_checkout_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Column metadata is read with `Statement::columns()` once a query has been executed. Each
+//! `Column` describes the column with the Oracle type name it would have in DDL (such as
+//! `VARCHAR2(256 CHAR)` or `NUMBER(10,2)`) and the Rust type its values are read as, and can be
+//! serialized with serde.
+use data::TypeInfo;
+use error::Result;
+use odpi::enums::{ODPINativeTypeNum, ODPIOracleTypeNum};
+use util::ODPIStr;
+
+/// The scale reported for numbers declared without a precision or scale, and for floats.
+const UNCONSTRAINED_SCALE: i8 = -127;
+
+/// A description of a column of a query.
+#[derive(Clone, Debug, Eq, Getters, PartialEq, Serialize)]
+pub struct Column {
+    /// The name of the column.
+    #[get = "pub"]
+    name: String,
+    /// The Oracle type of the column, as it would be written in DDL.
+    #[get = "pub"]
+    type_name: String,
+    /// The Rust type values of the column are read as.
+    #[get = "pub"]
+    rust_type: String,
+    /// Can the column contain nulls?
+    #[get = "pub"]
+    nullable: bool,
+    /// The precision of numeric columns, or the leading field precision of intervals.
+    #[get = "pub"]
+    precision: i16,
+    /// The scale of numeric columns.
+    #[get = "pub"]
+    scale: i8,
+    /// The fractional seconds precision of timestamp and interval columns.
+    #[get = "pub"]
+    fs_precision: u8,
+    /// The size of string columns in characters, or of binary columns in bytes.
+    #[get = "pub"]
+    size: u32,
+    /// The qualified name (`SCHEMA.NAME`) of the type of object columns.
+    #[get = "pub"]
+    object_type: Option<String>,
+}
+
+impl Column {
+    /// Create a new `Column` from query metadata.
+    ///
+    /// * `name` - the name of the column.
+    /// * `type_info` - the type of the column.
+    /// * `nullable` - can the column contain nulls?
+    #[doc(hidden)]
+    pub fn new(name: &str, type_info: &TypeInfo, nullable: bool) -> Result<Self> {
+        let object_type = match type_info.object_type() {
+            Some(object_type) => {
+                let info = object_type.get_info()?;
+                let schema: String = ODPIStr::new(info.schema, info.schema_length).into();
+                let name: String = ODPIStr::new(info.name, info.name_length).into();
+                Some(format!("{}.{}", schema, name))
+            }
+            None => None,
+        };
+
+        let size = match type_info.oracle_type_num() {
+            ODPIOracleTypeNum::Raw | ODPIOracleTypeNum::LongRaw => type_info.db_size_in_bytes(),
+            _ => type_info.size_in_chars(),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            type_name: type_name(type_info, object_type.as_ref()),
+            rust_type: rust_type(type_info).to_string(),
+            nullable,
+            precision: type_info.precision(),
+            scale: type_info.scale(),
+            fs_precision: type_info.fs_precision(),
+            size,
+            object_type,
+        })
+    }
+}
+
+/// Render the Oracle type name of a column.
+fn type_name(type_info: &TypeInfo, object_type: Option<&String>) -> String {
+    let precision = type_info.precision();
+    let scale = type_info.scale();
+    let fs_precision = type_info.fs_precision();
+    let chars = type_info.size_in_chars();
+
+    match type_info.oracle_type_num() {
+        ODPIOracleTypeNum::Varchar => format!("VARCHAR2({})", length(type_info)),
+        ODPIOracleTypeNum::NVarchar => format!("NVARCHAR2({})", chars),
+        ODPIOracleTypeNum::Char => format!("CHAR({})", length(type_info)),
+        ODPIOracleTypeNum::NChar => format!("NCHAR({})", chars),
+        ODPIOracleTypeNum::RowID => "ROWID".to_string(),
+        ODPIOracleTypeNum::Raw => format!("RAW({})", type_info.db_size_in_bytes()),
+        ODPIOracleTypeNum::NativeFloat => "BINARY_FLOAT".to_string(),
+        ODPIOracleTypeNum::NativeDouble => "BINARY_DOUBLE".to_string(),
+        ODPIOracleTypeNum::NativeInt | ODPIOracleTypeNum::NativeUint => "PLS_INTEGER".to_string(),
+        ODPIOracleTypeNum::Number => match (precision, scale) {
+            (0, UNCONSTRAINED_SCALE) | (0, 0) => "NUMBER".to_string(),
+            (precision, UNCONSTRAINED_SCALE) => format!("FLOAT({})", precision),
+            (precision, 0) => format!("NUMBER({})", precision),
+            (precision, scale) => format!("NUMBER({},{})", precision, scale),
+        },
+        ODPIOracleTypeNum::Date => "DATE".to_string(),
+        ODPIOracleTypeNum::Timestamp => format!("TIMESTAMP({})", fs_precision),
+        ODPIOracleTypeNum::TimestampTz => format!("TIMESTAMP({}) WITH TIME ZONE", fs_precision),
+        ODPIOracleTypeNum::TimestampLtz => {
+            format!("TIMESTAMP({}) WITH LOCAL TIME ZONE", fs_precision)
+        }
+        ODPIOracleTypeNum::IntervalDS => {
+            format!("INTERVAL DAY({}) TO SECOND({})", precision, fs_precision)
+        }
+        ODPIOracleTypeNum::IntervalYM => format!("INTERVAL YEAR({}) TO MONTH", precision),
+        ODPIOracleTypeNum::Clob => "CLOB".to_string(),
+        ODPIOracleTypeNum::NClob => "NCLOB".to_string(),
+        ODPIOracleTypeNum::Blob => "BLOB".to_string(),
+        ODPIOracleTypeNum::BFile => "BFILE".to_string(),
+        ODPIOracleTypeNum::Stmt => "REF CURSOR".to_string(),
+        ODPIOracleTypeNum::Boolean => "BOOLEAN".to_string(),
+        ODPIOracleTypeNum::Object => match object_type {
+            Some(name) => name.clone(),
+            None => "OBJECT".to_string(),
+        },
+        ODPIOracleTypeNum::LongVarchar => "LONG".to_string(),
+        ODPIOracleTypeNum::LongRaw => "LONG RAW".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Render the length of a VARCHAR2 or CHAR column with its length semantics. A column declared
+/// with character semantics takes more bytes than characters in a multibyte character set, so the
+/// length is given in characters if the sizes differ and in bytes otherwise.
+fn length(type_info: &TypeInfo) -> String {
+    let chars = type_info.size_in_chars();
+    let bytes = type_info.db_size_in_bytes();
+
+    if chars > 0 && bytes > chars {
+        format!("{} CHAR", chars)
+    } else {
+        format!("{} BYTE", bytes.max(chars))
+    }
+}
+
+/// Get the Rust type values of a column are read as, from its default native type.
+fn rust_type(type_info: &TypeInfo) -> &'static str {
+    match type_info.default_native_type_num() {
+        ODPINativeTypeNum::Int64 => "i64",
+        ODPINativeTypeNum::Uint64 => "u64",
+        ODPINativeTypeNum::Float => "f32",
+        ODPINativeTypeNum::Double => "f64",
+        ODPINativeTypeNum::Bytes => match type_info.oracle_type_num() {
+            ODPIOracleTypeNum::Raw | ODPIOracleTypeNum::LongRaw => "Vec<u8>",
+            _ => "String",
+        },
+        ODPINativeTypeNum::Timestamp => "DateTime<Utc>",
+        ODPINativeTypeNum::IntervalDS => "Duration",
+        ODPINativeTypeNum::IntervalYM => "YearsMonths",
+        ODPINativeTypeNum::Lob => match type_info.oracle_type_num() {
+            ODPIOracleTypeNum::Clob | ODPIOracleTypeNum::NClob => "String",
+            _ => "Vec<u8>",
+        },
+        ODPINativeTypeNum::Object => "Object",
+        ODPINativeTypeNum::Stmt => "Statement",
+        ODPINativeTypeNum::Boolean => "bool",
+        ODPINativeTypeNum::Rowid => "String",
+        ODPINativeTypeNum::Invalid => "()",
+    }
+}
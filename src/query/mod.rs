@@ -12,6 +12,8 @@ use data::TypeInfo;
 use odpi::structs::ODPIQueryInfo;
 use util::ODPIStr;
 
+pub mod column;
+
 /// This structure is used for passing query metadata from ODPI-C. It is populated by the function
 /// `Statement::get_query_info()`. All values remain valid as long as a reference is held to the
 /// statement and the statement is not re-executed or closed.
@@ -0,0 +1,140 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Support for pooling standalone connections with `r2d2`. This module is only available when the
+//! `r2d2` feature is enabled.
+use connection::Connection;
+use context::params::{CommonCreate, ConnCreate};
+use context::Context;
+use error::{Error, Result};
+use odpi::flags;
+use r2d2_crate::ManageConnection;
+use std::ops::{Deref, DerefMut};
+
+/// A standalone connection created by an `OracleConnectionManager`.
+pub struct OracleConnection {
+    /// The connection.
+    conn: Connection,
+}
+
+// Connections created by the manager use `DPI_MODE_CREATE_THREADED`, so they can be handed from one
+// thread to another. `r2d2` never uses a connection from more than one thread at a time.
+unsafe impl Send for OracleConnection {}
+
+impl Deref for OracleConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl DerefMut for OracleConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+}
+
+/// An `r2d2::ManageConnection` for standalone Oracle connections.
+pub struct OracleConnectionManager {
+    /// The context used to create connections.
+    context: Context,
+    /// The name of the user used for authenticating.
+    username: Option<String>,
+    /// The password used for authenticating.
+    password: Option<String>,
+    /// The connect string identifying the database.
+    connect_string: Option<String>,
+    /// The parameters used for creating each connection.
+    common_create_params: CommonCreate,
+    /// The connection parameters used for creating each connection.
+    conn_create_params: ConnCreate,
+}
+
+// The create parameters only point at data owned by the manager (or, for the string values set on
+// them, data the caller has guaranteed outlives the manager), and are only read when connecting.
+unsafe impl Send for OracleConnectionManager {}
+unsafe impl Sync for OracleConnectionManager {}
+
+impl OracleConnectionManager {
+    /// Create a new `OracleConnectionManager`, using the default create parameters of the given
+    /// context.
+    ///
+    /// * `context` - the context used to create connections.
+    /// * `username` - the name of the user used for authenticating the user, as a string in the
+    /// encoding used for CHAR data. None is also acceptable if external authentication is being
+    /// requested.
+    /// * `password` - the password to use for authenticating the user, as a string in the encoding
+    /// used for CHAR data. None is also acceptable if external authentication is being requested.
+    /// * `connect_string` - the connect string identifying the database to which a connection is to
+    /// be established, as a string in the encoding used for CHAR data. None is also acceptable for
+    /// local connections (identified by the environment variable $ORACLE_SID).
+    pub fn new(
+        context: Context,
+        username: Option<&str>,
+        password: Option<&str>,
+        connect_string: Option<&str>,
+    ) -> Result<Self> {
+        let common_create_params = context.init_common_create_params()?;
+        let conn_create_params = context.init_conn_create_params()?;
+
+        Ok(Self {
+            context,
+            username: username.map(|s| s.to_string()),
+            password: password.map(|s| s.to_string()),
+            connect_string: connect_string.map(|s| s.to_string()),
+            common_create_params,
+            conn_create_params,
+        })
+    }
+
+    /// Use the given `CommonCreate` parameters when creating connections. Any strings set on the
+    /// parameters with `set_edition()` or `set_driver_name()` must outlive the manager.
+    pub fn with_common_create_params(mut self, common_create_params: CommonCreate) -> Self {
+        self.common_create_params = common_create_params;
+        self
+    }
+
+    /// Use the given `ConnCreate` parameters when creating connections. Any strings set on the
+    /// parameters, e.g. with `set_connection_class()`, must outlive the manager.
+    pub fn with_conn_create_params(mut self, conn_create_params: ConnCreate) -> Self {
+        self.conn_create_params = conn_create_params;
+        self
+    }
+}
+
+impl ManageConnection for OracleConnectionManager {
+    type Connection = OracleConnection;
+    type Error = Error;
+
+    fn connect(&self) -> Result<OracleConnection> {
+        let mut comm_cp = CommonCreate::from(self.common_create_params.inner());
+        let create_mode = comm_cp.get_create_mode() | flags::DPI_MODE_CREATE_THREADED;
+        comm_cp.set_create_mode(create_mode);
+        let conn_cp = ConnCreate::new(self.conn_create_params.inner());
+
+        let conn = Connection::create(
+            &self.context,
+            self.username.as_ref().map(|s| s.as_str()),
+            self.password.as_ref().map(|s| s.as_str()),
+            self.connect_string.as_ref().map(|s| s.as_str()),
+            Some(comm_cp),
+            Some(conn_cp),
+        )?;
+
+        Ok(OracleConnection { conn })
+    }
+
+    fn is_valid(&self, conn: &mut OracleConnection) -> Result<()> {
+        conn.ping()
+    }
+
+    fn has_broken(&self, conn: &mut OracleConnection) -> bool {
+        conn.is_broken()
+    }
+}
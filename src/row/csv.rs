@@ -0,0 +1,246 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Rows are written as CSV with `Rows::write_csv()`, one line per row, optionally preceded by a
+//! header line with the column names. Fields containing the delimiter, the quote character or a
+//! line break are quoted, with quotes doubled, and so is any value that reads as the null marker.
+//! The same `CsvOptions` are used by `BulkLoader::from_csv()`, so a file written by one can be
+//! read by the other.
+//!
+//! Dates and timestamps are written with `CsvOptions::date_format`, intervals as ISO 8601
+//! durations, RAW and BLOB data as hexadecimal, and LOBs are streamed a chunk at a time (character
+//! LOBs are always quoted). Objects and cursors cannot be written as CSV.
+use chrono::NaiveDate;
+use error::{ErrorKind, Result};
+use lob::Lob;
+use odpi::enums::{ODPINativeTypeNum, ODPIOracleTypeNum};
+use odpi::structs::ODPITimestamp;
+use row::{Rows, LOB_CHUNK_SIZE};
+use serde::{iso8601_duration, iso8601_years_months};
+use std::io::Write;
+use types::Value;
+
+/// The format of dates and timestamps if no other format is given.
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// The number of rows loaded at a time by a `BulkLoader` if no other size is given.
+const DEFAULT_ARRAY_SIZE: u32 = 100;
+
+/// The options used to write and read CSV. These are usually built with a `CsvBuilder`.
+#[derive(Builder, Clone, Debug, Getters)]
+#[builder(name = "CsvBuilder", setter(into))]
+pub struct CsvOptions {
+    /// The character separating fields.
+    #[builder(default = "','")]
+    #[get = "pub"]
+    delimiter: char,
+    /// The character fields are quoted with.
+    #[builder(default = "'\"'")]
+    #[get = "pub"]
+    quote: char,
+    /// Is the first line a header with the column names?
+    #[builder(default = "true")]
+    #[get = "pub"]
+    has_headers: bool,
+    /// The text of a null field. A quoted field is never null.
+    #[builder(default)]
+    #[get = "pub"]
+    null_marker: String,
+    /// The chrono format (i.e. `%Y-%m-%d %H:%M:%S`) of dates and timestamps. When reading, a
+    /// format without a time gives midnight.
+    #[builder(default = "DEFAULT_DATE_FORMAT.to_string()")]
+    #[get = "pub"]
+    date_format: String,
+    /// The number of rows loaded at a time by a `BulkLoader`.
+    #[builder(default = "DEFAULT_ARRAY_SIZE")]
+    #[get = "pub"]
+    array_size: u32,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            has_headers: true,
+            null_marker: String::new(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            array_size: DEFAULT_ARRAY_SIZE,
+        }
+    }
+}
+
+/// Write the remaining rows as CSV, returning the number of rows written.
+#[doc(hidden)]
+pub fn write_csv<W: Write>(rows: &mut Rows, mut writer: W, options: &CsvOptions) -> Result<u64> {
+    if options.has_headers {
+        for (idx, name) in rows.column_names.iter().enumerate() {
+            if idx > 0 {
+                write!(writer, "{}", options.delimiter)?;
+            }
+            write_field(&mut writer, name, options)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+
+    let mut count = 0;
+    while !rows.done {
+        let (found, _) = rows.stmt.fetch()?;
+        if !found {
+            rows.done = true;
+            break;
+        }
+
+        write_row(rows, &mut writer, options)?;
+        writer.write_all(b"\n")?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Write the row the statement is positioned on as a line of CSV, without the line break.
+fn write_row<W: Write>(rows: &Rows, writer: &mut W, options: &CsvOptions) -> Result<()> {
+    for idx in 0..rows.column_names.len() {
+        if idx > 0 {
+            write!(writer, "{}", options.delimiter)?;
+        }
+
+        let (native_type_num, data) = rows.stmt.get_query_value(idx as u32 + 1)?;
+        let data = unsafe { &*data.inner() };
+        let oracle_type_num = rows.column_types[idx];
+
+        if data.is_null != 0 {
+            writer.write_all(options.null_marker.as_bytes())?;
+            continue;
+        }
+
+        // LOBs are streamed rather than read in full by `Value::from_data()`.
+        match native_type_num {
+            ODPINativeTypeNum::Lob => {
+                write_lob(writer, &Value::fetched_lob(data), oracle_type_num, options)?;
+            }
+            ODPINativeTypeNum::Timestamp => {
+                let timestamp = format_timestamp(unsafe { &data.value.as_timestamp }, options)?;
+                write_field(writer, &timestamp, options)?;
+            }
+            _ => {
+                let value = Value::from_data(data, oracle_type_num, native_type_num)?;
+                let field = match value {
+                    Value::Boolean(v) => v.to_string(),
+                    Value::Int64(v) => v.to_string(),
+                    Value::Uint64(v) => v.to_string(),
+                    Value::Float(v) => v.to_string(),
+                    Value::Double(v) => v.to_string(),
+                    Value::String(v) => v,
+                    Value::Bytes(v) => to_hex(&v),
+                    Value::IntervalDS(v) => iso8601_duration(&v),
+                    Value::IntervalYM(v) => iso8601_years_months(&v),
+                    other => {
+                        return Err(
+                            ErrorKind::FromSql(other.kind().to_string(), "CSV".to_string()).into(),
+                        )
+                    }
+                };
+                write_field(writer, &field, options)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a field, quoting it if needed.
+fn write_field<W: Write>(writer: &mut W, field: &str, options: &CsvOptions) -> Result<()> {
+    let needs_quotes = field
+        .contains(|c| c == options.delimiter || c == options.quote || c == '\n' || c == '\r')
+        || (!options.null_marker.is_empty() && field == options.null_marker);
+
+    if needs_quotes {
+        write!(writer, "{}", options.quote)?;
+        write_quoted(writer, field, options)?;
+        write!(writer, "{}", options.quote)?;
+    } else {
+        writer.write_all(field.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Write the contents of a quoted field, doubling any quotes.
+fn write_quoted<W: Write>(writer: &mut W, field: &str, options: &CsvOptions) -> Result<()> {
+    let quote = options.quote.to_string();
+    let doubled = format!("{}{}", quote, quote);
+    writer.write_all(field.replace(&quote, &doubled).as_bytes())?;
+    Ok(())
+}
+
+/// Write the contents of a LOB, a chunk at a time. Character LOBs are written quoted, and all other
+/// LOBs as hexadecimal.
+fn write_lob<W: Write>(
+    writer: &mut W,
+    lob: &Lob,
+    oracle_type_num: ODPIOracleTypeNum,
+    options: &CsvOptions,
+) -> Result<()> {
+    let size = lob.get_size()?;
+    let is_char = match oracle_type_num {
+        ODPIOracleTypeNum::Clob | ODPIOracleTypeNum::NClob => true,
+        _ => false,
+    };
+
+    if is_char {
+        write!(writer, "{}", options.quote)?;
+    }
+    let mut offset = 0;
+    while offset < size {
+        let amount = LOB_CHUNK_SIZE.min(size - offset);
+        let chunk: Vec<u8> = lob
+            .read_bytes(offset + 1, amount)?
+            .into_iter()
+            .map(|byte| byte as u8)
+            .collect();
+
+        if is_char {
+            write_quoted(writer, &String::from_utf8_lossy(&chunk), options)?;
+        } else {
+            writer.write_all(to_hex(&chunk).as_bytes())?;
+        }
+        offset += amount;
+    }
+    if is_char {
+        write!(writer, "{}", options.quote)?;
+    }
+
+    Ok(())
+}
+
+/// Format an ODPI-C timestamp with the date format of the options. Any time zone is ignored.
+fn format_timestamp(timestamp: &ODPITimestamp, options: &CsvOptions) -> Result<String> {
+    let timestamp = NaiveDate::from_ymd_opt(
+        i32::from(timestamp.year),
+        u32::from(timestamp.month),
+        u32::from(timestamp.day),
+    )
+    .and_then(|date| {
+        date.and_hms_nano_opt(
+            u32::from(timestamp.hour),
+            u32::from(timestamp.minute),
+            u32::from(timestamp.second),
+            timestamp.fsecond,
+        )
+    })
+    .ok_or(ErrorKind::Conversion)?;
+
+    Ok(timestamp.format(&options.date_format).to_string())
+}
+
+/// Encode bytes as upper case hexadecimal, the format Oracle converts RAW data to and from.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
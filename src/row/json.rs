@@ -0,0 +1,275 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Rows are written as JSON with `Rows::write_json()`, as an array of objects, or with
+//! `Rows::write_ndjson()`, as one object per line. Each object is keyed by column name, in column
+//! order. Values are read straight from the fetched row and written as they are read, so LOBs are
+//! streamed a chunk at a time rather than buffered, and can be truncated with
+//! `JsonOptions::lob_limit`.
+//!
+//! Numbers and booleans are written as JSON numbers and booleans, with infinite and NaN floating
+//! point numbers written as null. Timestamps are written as RFC 3339 strings in the time zone they
+//! were fetched in, intervals as ISO 8601 durations (`P1DT2H3M4.5S` or `P1Y2M`), RAW and BLOB data
+//! as base64 strings, ROWIDs as strings, objects as JSON objects keyed by attribute name, and
+//! cursors as arrays of rows.
+use chrono::{FixedOffset, TimeZone};
+use error::{ErrorKind, Result};
+use lob::Lob;
+use odpi::enums::{ODPINativeTypeNum, ODPIOracleTypeNum};
+use odpi::structs::ODPITimestamp;
+use row::{Rows, LOB_CHUNK_SIZE};
+use serde::{iso8601_duration, iso8601_years_months};
+use std::io::Write;
+use types::Value;
+use util::base64_encode;
+
+/// The options used when writing rows as JSON. These are usually built with a `JsonBuilder`.
+#[derive(Builder, Clone, Copy, Debug, Default, Getters)]
+#[builder(name = "JsonBuilder")]
+pub struct JsonOptions {
+    /// The maximum number of characters (for character LOBs) or bytes (for binary LOBs) written
+    /// for each LOB. Longer LOBs are truncated. LOBs are written in full if this is None.
+    #[builder(default)]
+    #[get = "pub"]
+    lob_limit: Option<u64>,
+}
+
+impl Value {
+    /// Encode the value as JSON, with the same encodings used by `Rows::write_json()`.
+    pub fn to_json(self) -> Result<String> {
+        let mut json = Vec::new();
+        write_value(&mut json, self, &JsonOptions::default())?;
+        Ok(String::from_utf8_lossy(&json).into_owned())
+    }
+}
+
+/// Write the remaining rows as a JSON array of objects, returning the number of rows written.
+#[doc(hidden)]
+pub fn write_json<W: Write>(rows: &mut Rows, mut writer: W, options: &JsonOptions) -> Result<u64> {
+    writer.write_all(b"[")?;
+    let count = write_rows(rows, &mut writer, options, false)?;
+    writer.write_all(b"]")?;
+    Ok(count)
+}
+
+/// Write the remaining rows as JSON objects, one per line, returning the number of rows written.
+#[doc(hidden)]
+pub fn write_ndjson<W: Write>(
+    rows: &mut Rows,
+    mut writer: W,
+    options: &JsonOptions,
+) -> Result<u64> {
+    write_rows(rows, &mut writer, options, true)
+}
+
+/// Fetch and write each remaining row, separated by commas or terminated by newlines.
+fn write_rows<W: Write>(
+    rows: &mut Rows,
+    writer: &mut W,
+    options: &JsonOptions,
+    ndjson: bool,
+) -> Result<u64> {
+    let mut count = 0;
+
+    while !rows.done {
+        let (found, _) = rows.stmt.fetch()?;
+        if !found {
+            rows.done = true;
+            break;
+        }
+
+        if count > 0 && !ndjson {
+            writer.write_all(b",")?;
+        }
+        write_row(rows, writer, options)?;
+        if ndjson {
+            writer.write_all(b"\n")?;
+        }
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Write the row the statement is positioned on as a JSON object.
+fn write_row<W: Write>(rows: &Rows, writer: &mut W, options: &JsonOptions) -> Result<()> {
+    writer.write_all(b"{")?;
+
+    for (idx, name) in rows.column_names.iter().enumerate() {
+        if idx > 0 {
+            writer.write_all(b",")?;
+        }
+        write_string(writer, name)?;
+        writer.write_all(b":")?;
+
+        let (native_type_num, data) = rows.stmt.get_query_value(idx as u32 + 1)?;
+        let data = unsafe { &*data.inner() };
+        let oracle_type_num = rows.column_types[idx];
+
+        if data.is_null != 0 {
+            writer.write_all(b"null")?;
+            continue;
+        }
+
+        // LOBs are streamed rather than read in full by `Value::from_data()`.
+        match native_type_num {
+            ODPINativeTypeNum::Lob => {
+                write_lob(writer, &Value::fetched_lob(data), oracle_type_num, options)?;
+            }
+            ODPINativeTypeNum::Timestamp => {
+                write_timestamp(writer, unsafe { &data.value.as_timestamp })?;
+            }
+            ODPINativeTypeNum::Object => match rows.object_types[idx] {
+                Some(ref object_type) => {
+                    let value = Value::from_fetched_object(data, object_type)?;
+                    write_value(writer, value, options)?;
+                }
+                None => {
+                    return Err(ErrorKind::FromSql("object".to_string(), "JSON".to_string()).into())
+                }
+            },
+            _ => {
+                let value = Value::from_data(data, oracle_type_num, native_type_num)?;
+                write_value(writer, value, options)?;
+            }
+        }
+    }
+
+    writer.write_all(b"}")?;
+    Ok(())
+}
+
+/// Write a value as JSON.
+fn write_value<W: Write>(writer: &mut W, value: Value, options: &JsonOptions) -> Result<()> {
+    match value {
+        Value::Null => writer.write_all(b"null")?,
+        Value::Boolean(v) => writer.write_all(if v { b"true" } else { b"false" })?,
+        Value::Int64(v) => write!(writer, "{}", v)?,
+        Value::Uint64(v) => write!(writer, "{}", v)?,
+        Value::Float(v) if v.is_finite() => write!(writer, "{}", v)?,
+        Value::Double(v) if v.is_finite() => write!(writer, "{}", v)?,
+        Value::Float(_) | Value::Double(_) => writer.write_all(b"null")?,
+        Value::String(v) => write_string(writer, &v)?,
+        Value::Bytes(v) => write_string(writer, &base64_encode(&v))?,
+        Value::Timestamp(v) => write_string(writer, &v.to_rfc3339())?,
+        Value::IntervalDS(v) => write_string(writer, &iso8601_duration(&v))?,
+        Value::IntervalYM(v) => write_string(writer, &iso8601_years_months(&v))?,
+        Value::Object(attributes) => {
+            writer.write_all(b"{")?;
+            for (idx, (name, value)) in attributes.into_iter().enumerate() {
+                if idx > 0 {
+                    writer.write_all(b",")?;
+                }
+                write_string(writer, &name)?;
+                writer.write_all(b":")?;
+                write_value(writer, value, options)?;
+            }
+            writer.write_all(b"}")?;
+        }
+        Value::Cursor(stmt) => {
+            let mut rows = Rows::new(stmt)?;
+            writer.write_all(b"[")?;
+            write_rows(&mut rows, writer, options, false)?;
+            writer.write_all(b"]")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the contents of a LOB as a JSON string, a chunk at a time. Character LOBs are written as
+/// text and all other LOBs as base64.
+fn write_lob<W: Write>(
+    writer: &mut W,
+    lob: &Lob,
+    oracle_type_num: ODPIOracleTypeNum,
+    options: &JsonOptions,
+) -> Result<()> {
+    let size = lob.get_size()?;
+    let length = options.lob_limit.map_or(size, |limit| limit.min(size));
+    let is_char = match oracle_type_num {
+        ODPIOracleTypeNum::Clob | ODPIOracleTypeNum::NClob => true,
+        _ => false,
+    };
+
+    writer.write_all(b"\"")?;
+    let mut offset = 0;
+    while offset < length {
+        let amount = LOB_CHUNK_SIZE.min(length - offset);
+        let chunk: Vec<u8> = lob
+            .read_bytes(offset + 1, amount)?
+            .into_iter()
+            .map(|byte| byte as u8)
+            .collect();
+
+        if is_char {
+            write_escaped(writer, &String::from_utf8_lossy(&chunk))?;
+        } else {
+            writer.write_all(base64_encode(&chunk).as_bytes())?;
+        }
+        offset += amount;
+    }
+    writer.write_all(b"\"")?;
+
+    Ok(())
+}
+
+/// Write an ODPI-C timestamp as an RFC 3339 string, in the time zone it was fetched in.
+fn write_timestamp<W: Write>(writer: &mut W, timestamp: &ODPITimestamp) -> Result<()> {
+    let offset =
+        i32::from(timestamp.tz_hour_offset) * 3600 + i32::from(timestamp.tz_minute_offset) * 60;
+    let timestamp = FixedOffset::east_opt(offset)
+        .and_then(|offset| {
+            offset
+                .ymd_opt(
+                    i32::from(timestamp.year),
+                    u32::from(timestamp.month),
+                    u32::from(timestamp.day),
+                )
+                .single()
+        })
+        .and_then(|date| {
+            date.and_hms_nano_opt(
+                u32::from(timestamp.hour),
+                u32::from(timestamp.minute),
+                u32::from(timestamp.second),
+                timestamp.fsecond,
+            )
+        })
+        .ok_or(ErrorKind::Conversion)?;
+
+    write_string(writer, &timestamp.to_rfc3339())
+}
+
+/// Write a JSON string.
+fn write_string<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+    writer.write_all(b"\"")?;
+    write_escaped(writer, value)?;
+    writer.write_all(b"\"")?;
+    Ok(())
+}
+
+/// Write the contents of a JSON string, escaping quotes, backslashes and control characters.
+fn write_escaped<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c < ' ' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    writer.write_all(escaped.as_bytes())?;
+    Ok(())
+}
@@ -0,0 +1,236 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A `Row` holds the values of one row, read out of the ODPI-C buffers so that it remains valid
+//! after the statement moves on. Values are addressed by column index, starting from 0, and are
+//! converted to Rust types with `FromSql`. `Rows` iterates over the rows of an executed query,
+//! including REF CURSORs and nested cursors, which are read from a row with `Row::get_cursor()`.
+use error::{ErrorKind, Result};
+use objecttype::ObjectType;
+use odpi::enums::ODPIOracleTypeNum;
+use row::csv::CsvOptions;
+use row::json::JsonOptions;
+use serde::DeserializeRows;
+use serde_crate::de::DeserializeOwned;
+use statement::Statement;
+use std::io::Write;
+use types::{FromSql, Value};
+
+pub mod csv;
+pub mod json;
+pub mod scrollable;
+
+/// The number of characters or bytes read from a LOB at a time when rows are exported. This is a
+/// multiple of 3, so each chunk of a binary LOB can be base64 encoded on its own.
+const LOB_CHUNK_SIZE: u64 = 32_766;
+
+/// The values of a single row.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Row {
+    /// The value of each column.
+    values: Vec<Value>,
+}
+
+impl Row {
+    /// Create a new `Row` from the value of each column.
+    #[doc(hidden)]
+    pub fn new(values: Vec<Value>) -> Self {
+        Self { values }
+    }
+
+    /// Get the value of the column at the given index, converted to `T`.
+    ///
+    /// * `idx` - the index of the column, starting from 0.
+    pub fn get<T: FromSql>(&self, idx: usize) -> Result<T> {
+        T::from_sql(self.value(idx)?.clone())
+    }
+
+    /// Get the REF CURSOR or nested cursor (`CURSOR(...)` expression) in the column at the given
+    /// index, as rows that can be iterated. The cursor has its own fetch array size, which can be
+    /// changed with `Rows::set_fetch_array_size()` before the first row is fetched.
+    ///
+    /// * `idx` - the index of the column, starting from 0.
+    pub fn get_cursor(&self, idx: usize) -> Result<Rows> {
+        Rows::new(self.get::<Statement>(idx)?)
+    }
+
+    /// Get the value of the column at the given index.
+    ///
+    /// * `idx` - the index of the column, starting from 0.
+    pub fn value(&self, idx: usize) -> Result<&Value> {
+        self.values
+            .get(idx)
+            .ok_or_else(|| ErrorKind::ColumnIndex(idx).into())
+    }
+
+    /// Get the value of each column.
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    /// Consume the row, returning the value of each column.
+    pub fn into_values(self) -> Vec<Value> {
+        self.values
+    }
+
+    /// Get the number of columns.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the row has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// The rows of an executed query, fetched one at a time.
+pub struct Rows {
+    /// The executed query.
+    stmt: Statement,
+    /// The name of each column.
+    column_names: Vec<String>,
+    /// The Oracle type of each column.
+    column_types: Vec<ODPIOracleTypeNum>,
+    /// The object type of each object column.
+    object_types: Vec<Option<ObjectType>>,
+    /// Have all of the rows been fetched?
+    done: bool,
+}
+
+impl Rows {
+    /// Create a new `Rows` over a statement that has been executed, or a REF CURSOR.
+    ///
+    /// * `stmt` - the statement to fetch rows from.
+    pub fn new(stmt: Statement) -> Result<Self> {
+        let num_cols = stmt.get_num_query_columns()?;
+        let mut column_names = Vec::with_capacity(num_cols as usize);
+        let mut column_types = Vec::with_capacity(num_cols as usize);
+        let mut object_types = Vec::with_capacity(num_cols as usize);
+
+        for pos in 1..=num_cols {
+            let info = stmt.get_query_info(pos)?;
+            column_names.push(info.name());
+            column_types.push(info.type_info().oracle_type_num());
+            object_types.push(info.type_info().object_type());
+        }
+
+        Ok(Self {
+            stmt,
+            column_names,
+            column_types,
+            object_types,
+            done: false,
+        })
+    }
+
+    /// Get the statement the rows are fetched from.
+    pub fn statement(&self) -> &Statement {
+        &self.stmt
+    }
+
+    /// Get the name of each column.
+    pub fn column_names(&self) -> &[String] {
+        &self.column_names
+    }
+
+    /// Sets the number of rows fetched from the database in each round trip. This only affects
+    /// these rows, and not the rows of the statement a nested cursor was selected from.
+    ///
+    /// * `array_size` - the fetch array size. A value of zero resets it to the default.
+    pub fn set_fetch_array_size(&self, array_size: u32) -> Result<()> {
+        self.stmt.set_fetch_array_size(array_size)
+    }
+
+    /// Deserialize each remaining row into a `T`, matching struct fields to column names without
+    /// regard to case. See the `serde` module for how values are converted.
+    pub fn deserialize<T: DeserializeOwned>(self) -> DeserializeRows<T> {
+        DeserializeRows::new(self)
+    }
+
+    /// Write each remaining row as a line of CSV, after a header line with the column names if
+    /// the options ask for one. Returns the number of rows written. See the `csv` module for how
+    /// values are written.
+    ///
+    /// * `writer` - where the CSV is written.
+    /// * `options` - the options used to write the rows.
+    pub fn write_csv<W: Write>(&mut self, writer: W, options: &CsvOptions) -> Result<u64> {
+        csv::write_csv(self, writer, options)
+    }
+
+    /// Write each remaining row as a JSON object keyed by column name, inside a JSON array.
+    /// Returns the number of rows written. See the `json` module for how values are encoded.
+    ///
+    /// * `writer` - where the JSON is written.
+    /// * `options` - the options used to write the rows.
+    pub fn write_json<W: Write>(&mut self, writer: W, options: &JsonOptions) -> Result<u64> {
+        json::write_json(self, writer, options)
+    }
+
+    /// Write each remaining row as a JSON object keyed by column name, followed by a newline.
+    /// Returns the number of rows written.
+    ///
+    /// * `writer` - where the JSON is written.
+    /// * `options` - the options used to write the rows.
+    pub fn write_ndjson<W: Write>(&mut self, writer: W, options: &JsonOptions) -> Result<u64> {
+        json::write_ndjson(self, writer, options)
+    }
+
+    /// Fetch the next row, or None if all of the rows have been fetched.
+    pub fn next_row(&mut self) -> Result<Option<Row>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let (found, _) = self.stmt.fetch()?;
+        if !found {
+            self.done = true;
+            return Ok(None);
+        }
+
+        self.current_row().map(Some)
+    }
+
+    /// Read the values of the row the statement is positioned on.
+    #[doc(hidden)]
+    pub fn current_row(&self) -> Result<Row> {
+        let mut values = Vec::with_capacity(self.column_types.len());
+
+        for (pos, (oracle_type_num, object_type)) in
+            (1..).zip(self.column_types.iter().zip(&self.object_types))
+        {
+            let (native_type_num, data) = self.stmt.get_query_value(pos)?;
+            let data = unsafe { &*data.inner() };
+
+            let value = match *object_type {
+                Some(ref object_type) if data.is_null == 0 => {
+                    Value::from_fetched_object(data, object_type)?
+                }
+                _ => Value::from_data(data, *oracle_type_num, native_type_num)?,
+            };
+            values.push(value);
+        }
+
+        Ok(Row::new(values))
+    }
+}
+
+impl Iterator for Rows {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_row() {
+            Ok(Some(row)) => Some(Ok(row)),
+            Ok(None) => None,
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
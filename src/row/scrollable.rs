@@ -0,0 +1,134 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Scrollable rows move around the result set of a query prepared as scrollable. Every move
+//! scrolls the cursor to an absolute row number and fetches exactly that row, so the statement's
+//! row count always matches the current position and no `row_count_offset` has to be given to
+//! `Statement::scroll()`. The number of rows is found by scrolling to the last row the first time
+//! it is needed, which allows moves outside of the result set to return None rather than an error.
+use error::Result;
+use odpi::enums::ODPIFetchMode;
+use row::{Row, Rows};
+use statement::Statement;
+use std::convert::TryFrom;
+
+/// The rows of an executed scrollable query, which can be fetched in any order.
+pub struct ScrollableRows {
+    /// The rows of the query.
+    rows: Rows,
+    /// The number of the current row, starting from 1, or 0 before the first row is fetched.
+    position: u64,
+    /// The number of rows in the result set, once it is known.
+    num_rows: Option<u64>,
+}
+
+impl ScrollableRows {
+    /// Create a new `ScrollableRows` over a statement that was prepared as scrollable and has been
+    /// executed.
+    ///
+    /// * `stmt` - the statement to fetch rows from.
+    pub fn new(stmt: Statement) -> Result<Self> {
+        Ok(Self {
+            rows: Rows::new(stmt)?,
+            position: 0,
+            num_rows: None,
+        })
+    }
+
+    /// Get the statement the rows are fetched from.
+    pub fn statement(&self) -> &Statement {
+        self.rows.statement()
+    }
+
+    /// Get the name of each column.
+    pub fn column_names(&self) -> &[String] {
+        self.rows.column_names()
+    }
+
+    /// Get the number of the current row, starting from 1, or 0 if no row has been fetched.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Get the number of rows in the result set. The first call scrolls to the last row.
+    pub fn num_rows(&mut self) -> Result<u64> {
+        if let Some(num_rows) = self.num_rows {
+            return Ok(num_rows);
+        }
+
+        self.statement().scroll(ODPIFetchMode::Last, 0, 0)?;
+        let (found, _) = self.statement().fetch()?;
+        let num_rows = if found {
+            self.statement().get_row_count()?
+        } else {
+            0
+        };
+
+        self.position = num_rows;
+        self.num_rows = Some(num_rows);
+        Ok(num_rows)
+    }
+
+    /// Fetch the first row, or None if there are no rows.
+    pub fn first(&mut self) -> Result<Option<Row>> {
+        self.fetch_at(1)
+    }
+
+    /// Fetch the last row, or None if there are no rows.
+    pub fn last(&mut self) -> Result<Option<Row>> {
+        let num_rows = self.num_rows()?;
+        self.fetch_at(num_rows as i64)
+    }
+
+    /// Fetch the row before the current row, or None if the current row is the first.
+    pub fn prior(&mut self) -> Result<Option<Row>> {
+        let target = self.position as i64 - 1;
+        self.fetch_at(target)
+    }
+
+    /// Fetch the row after the current row, or None if the current row is the last.
+    pub fn next_row(&mut self) -> Result<Option<Row>> {
+        let target = self.position as i64 + 1;
+        self.fetch_at(target)
+    }
+
+    /// Fetch the row with the given number, or None if there is no such row.
+    ///
+    /// * `row` - the number of the row, starting from 1.
+    pub fn absolute(&mut self, row: i32) -> Result<Option<Row>> {
+        self.fetch_at(i64::from(row))
+    }
+
+    /// Fetch the row at the given offset from the current row, or None if there is no such row.
+    ///
+    /// * `offset` - the number of rows to move. A positive number moves forward in the result set
+    /// while a negative number moves backwards.
+    pub fn relative(&mut self, offset: i32) -> Result<Option<Row>> {
+        let target = self.position as i64 + i64::from(offset);
+        self.fetch_at(target)
+    }
+
+    /// Scroll to the given row and fetch it. The current position is unchanged if the row is
+    /// outside of the result set.
+    fn fetch_at(&mut self, target: i64) -> Result<Option<Row>> {
+        let num_rows = self.num_rows()?;
+        if target < 1 || target as u64 > num_rows {
+            return Ok(None);
+        }
+
+        self.statement()
+            .scroll(ODPIFetchMode::Absolute, TryFrom::try_from(target)?, 0)?;
+        let (found, _) = self.statement().fetch()?;
+        if !found {
+            return Ok(None);
+        }
+
+        self.position = target as u64;
+        self.rows.current_row().map(Some)
+    }
+}
@@ -0,0 +1,347 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Rows are deserialized into user types with serde, using `Rows::deserialize()` or `from_row()`.
+//! A row deserializes as a map from column name to value. Struct fields are matched to columns by
+//! name, ignoring case, so that a field `username` is filled from the column `USERNAME`. Columns
+//! without a matching field are ignored, and `Option` fields may be missing or null. A row can
+//! also be deserialized into a tuple, in which case the columns are taken in order.
+//!
+//! Numbers are converted with the same rules as `FromSql`, so a `NUMBER` column fetched as a
+//! double can fill an integer field if it holds an integer. Dates and timestamps are presented as
+//! RFC 3339 strings, which is the format chrono deserializes `DateTime<Utc>` from, intervals as
+//! ISO 8601 durations, and object columns as maps from attribute name to value.
+//!
+//! In the other direction, `Statement::bind_struct()` binds the fields of a struct to the named
+//! placeholders of a statement, and `to_values()` gives the values that would be bound. Fields
+//! annotated with `#[serde(with = "mimir::serde::timestamp")]` are bound as timestamps.
+use chrono::Duration;
+use data::YearsMonths;
+use error::{Error, ErrorKind, Result};
+use row::{Row, Rows};
+use serde_crate::de::value::{MapDeserializer, SeqDeserializer};
+use serde_crate::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use std::fmt;
+use std::marker::PhantomData;
+use types::{FromSql, Value};
+
+mod ser;
+pub mod timestamp;
+
+pub use self::ser::{bind_struct, to_values};
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ErrorKind::Deserialize(msg.to_string()).into()
+    }
+}
+
+/// Deserialize a row into a `T`.
+///
+/// * `row` - the row to deserialize.
+/// * `columns` - the name of each column of the row.
+pub fn from_row<T: DeserializeOwned>(row: Row, columns: &[String]) -> Result<T> {
+    T::deserialize(RowDeserializer {
+        entries: columns.iter().cloned().zip(row.into_values()).collect(),
+    })
+}
+
+/// An iterator that deserializes each row of a query into a `T`, created with
+/// `Rows::deserialize()`.
+pub struct DeserializeRows<T> {
+    /// The rows being deserialized.
+    rows: Rows,
+    /// The type the rows are deserialized into.
+    phantom: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> DeserializeRows<T> {
+    /// Create a new `DeserializeRows`.
+    #[doc(hidden)]
+    pub fn new(rows: Rows) -> Self {
+        Self {
+            rows,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for DeserializeRows<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.rows.next() {
+            Some(Ok(row)) => Some(from_row(row, self.rows.column_names())),
+            Some(Err(e)) => Some(Err(e)),
+            None => None,
+        }
+    }
+}
+
+/// Format a duration as an ISO 8601 duration (i.e. `P1DT2H3M4.5S`).
+#[doc(hidden)]
+pub fn iso8601_duration(duration: &Duration) -> String {
+    let sign = if *duration < Duration::zero() {
+        "-"
+    } else {
+        ""
+    };
+    let duration = if *duration < Duration::zero() {
+        -*duration
+    } else {
+        *duration
+    };
+
+    let days = duration.num_days();
+    let hours = duration.num_hours() % 24;
+    let minutes = duration.num_minutes() % 60;
+    let seconds = duration.num_seconds() % 60;
+    let nanos = (duration - Duration::seconds(duration.num_seconds()))
+        .num_nanoseconds()
+        .unwrap_or(0);
+
+    let fraction = if nanos == 0 {
+        String::new()
+    } else {
+        format!(".{:09}", nanos).trim_right_matches('0').to_string()
+    };
+
+    format!(
+        "{}P{}DT{}H{}M{}{}S",
+        sign, days, hours, minutes, seconds, fraction
+    )
+}
+
+/// Format a year to month interval as an ISO 8601 duration (i.e. `P1Y2M`).
+#[doc(hidden)]
+pub fn iso8601_years_months(interval: &YearsMonths) -> String {
+    let (years, months) = (*interval.years(), *interval.months());
+    if years < 0 || months < 0 {
+        format!("-P{}Y{}M", -years, -months)
+    } else {
+        format!("P{}Y{}M", years, months)
+    }
+}
+
+/// Match each entry to a struct field by name, ignoring case, and visit the entries as a map.
+fn visit_entries<'de, V: Visitor<'de>>(
+    entries: Vec<(String, Value)>,
+    fields: &'static [&'static str],
+    visitor: V,
+) -> Result<V::Value> {
+    let entries = entries.into_iter().map(|(name, value)| {
+        let key = fields
+            .iter()
+            .find(|field| field.eq_ignore_ascii_case(&name))
+            .map(|field| field.to_string())
+            .unwrap_or(name);
+        (key, ValueDeserializer(value))
+    });
+    visitor.visit_map(MapDeserializer::new(entries))
+}
+
+/// Deserializes a row, as a map from column name to value or as a sequence of values.
+struct RowDeserializer {
+    /// The name and value of each column.
+    entries: Vec<(String, Value)>,
+}
+
+impl<'de> de::Deserializer<'de> for RowDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let entries = self
+            .entries
+            .into_iter()
+            .map(|(name, value)| (name, ValueDeserializer(value)));
+        visitor.visit_map(MapDeserializer::new(entries))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visit_entries(self.entries, fields, visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let values = self
+            .entries
+            .into_iter()
+            .map(|(_, value)| ValueDeserializer(value));
+        visitor.visit_seq(SeqDeserializer::new(values))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct map enum identifier ignored_any
+    }
+}
+
+/// Deserializes a single value.
+struct ValueDeserializer(Value);
+
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::Null => visitor.visit_unit(),
+            Value::Boolean(v) => visitor.visit_bool(v),
+            Value::Int64(v) => visitor.visit_i64(v),
+            Value::Uint64(v) => visitor.visit_u64(v),
+            Value::Float(v) => visitor.visit_f32(v),
+            Value::Double(v) => visitor.visit_f64(v),
+            Value::String(v) => visitor.visit_string(v),
+            Value::Bytes(v) => visitor.visit_byte_buf(v),
+            Value::Timestamp(v) => visitor.visit_string(v.to_rfc3339()),
+            Value::IntervalDS(v) => visitor.visit_string(iso8601_duration(&v)),
+            Value::IntervalYM(v) => visitor.visit_string(iso8601_years_months(&v)),
+            Value::Object(attributes) => {
+                let entries = attributes
+                    .into_iter()
+                    .map(|(name, value)| (name, ValueDeserializer(value)));
+                visitor.visit_map(MapDeserializer::new(entries))
+            }
+            Value::Cursor(_) => {
+                Err(ErrorKind::FromSql("cursor".to_string(), "a serde value".to_string()).into())
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_bool(bool::from_sql(self.0)?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i64(i64::from_sql(self.0)?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i64(i64::from_sql(self.0)?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i64(i64::from_sql(self.0)?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i64(i64::from_sql(self.0)?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u64(u64::from_sql(self.0)?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u64(u64::from_sql(self.0)?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u64(u64::from_sql(self.0)?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u64(u64::from_sql(self.0)?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(f64::from_sql(self.0)?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(f64::from_sql(self.0)?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::Bytes(v) => visitor.visit_byte_buf(v),
+            Value::IntervalDS(v) => visitor.visit_string(iso8601_duration(&v)),
+            Value::IntervalYM(v) => visitor.visit_string(iso8601_years_months(&v)),
+            value => visitor.visit_string(String::from_sql(value)?),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::String(v) => visitor.visit_byte_buf(v.into_bytes()),
+            value => visitor.visit_byte_buf(Vec::<u8>::from_sql(value)?),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.0 {
+            Value::Object(attributes) => visit_entries(attributes, fields, visitor),
+            value => Err(ErrorKind::FromSql(value_kind(&value), name.to_string()).into()),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 char unit unit_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Describe a value that cannot be deserialized as a struct.
+fn value_kind(value: &Value) -> String {
+    match *value {
+        Value::Null => "null",
+        _ => "non-object",
+    }
+    .to_string()
+}
@@ -0,0 +1,580 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Structs are bound to the named placeholders of a statement with `Statement::bind_struct()`.
+//! The struct is serialized into a list of fields, and each field is bound by value to the
+//! placeholder with the same name, ignoring case. Every placeholder must have a field and every
+//! field must have a placeholder. Field values must be scalars: `None` is bound as a null, and a
+//! `DateTime<Utc>` serialized with the `timestamp` module as a timestamp.
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use error::{Error, ErrorKind, Result};
+use odpi::enums::ODPINativeTypeNum;
+use odpi::structs::{
+    ODPIBytes, ODPIData, ODPIDataBuffer, ODPIIntervalDS, ODPIIntervalYM, ODPITimestamp,
+};
+use serde::timestamp::TIMESTAMP;
+use serde_crate::ser::{self, Impossible, Serialize};
+use statement::Statement;
+use std::convert::TryFrom;
+use std::fmt;
+use std::os::raw::c_char;
+use types::Value;
+
+/// The encoding given with string values bound to a statement.
+const ENCODING: &[u8] = b"UTF-8\0";
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ErrorKind::Serialize(msg.to_string()).into()
+    }
+}
+
+/// Serialize a struct, or a map with string keys, into the name and value of each field.
+pub fn to_values<T: Serialize>(value: &T) -> Result<Vec<(String, Value)>> {
+    value.serialize(FieldsSerializer)
+}
+
+/// Bind each field of a struct to the placeholder of the statement with the same name.
+#[doc(hidden)]
+pub fn bind_struct<T: Serialize>(stmt: &Statement, value: &T) -> Result<()> {
+    let fields = to_values(value)?;
+    let names = stmt.get_bind_names(stmt.get_bind_count()?)?;
+
+    if let Some(name) = names.iter().find(|name| {
+        !fields
+            .iter()
+            .any(|(field, _)| field.eq_ignore_ascii_case(name))
+    }) {
+        return Err(ErrorKind::BindField(name.clone()).into());
+    }
+
+    for (field, value) in &fields {
+        match names.iter().find(|name| name.eq_ignore_ascii_case(field)) {
+            Some(name) => bind_value(stmt, name, value)?,
+            None => return Err(ErrorKind::UnboundField(field.clone()).into()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Bind a value to the placeholder with the given name. The value is copied into a variable
+/// created by ODPI-C, so it does not need to outlive the call.
+fn bind_value(stmt: &Statement, name: &str, value: &Value) -> Result<()> {
+    let (native_type, is_null, buffer) = match *value {
+        Value::Null => (
+            ODPINativeTypeNum::Bytes,
+            true,
+            ODPIDataBuffer { as_boolean: 0 },
+        ),
+        Value::Boolean(v) => (
+            ODPINativeTypeNum::Boolean,
+            false,
+            ODPIDataBuffer {
+                as_boolean: if v { 1 } else { 0 },
+            },
+        ),
+        Value::Int64(v) => (
+            ODPINativeTypeNum::Int64,
+            false,
+            ODPIDataBuffer { as_int_64: v },
+        ),
+        Value::Uint64(v) => (
+            ODPINativeTypeNum::Uint64,
+            false,
+            ODPIDataBuffer { as_uint_64: v },
+        ),
+        Value::Float(v) => (
+            ODPINativeTypeNum::Float,
+            false,
+            ODPIDataBuffer { as_float: v },
+        ),
+        Value::Double(v) => (
+            ODPINativeTypeNum::Double,
+            false,
+            ODPIDataBuffer { as_double: v },
+        ),
+        Value::String(ref v) => (ODPINativeTypeNum::Bytes, false, bytes(v.as_bytes())?),
+        Value::Bytes(ref v) => (ODPINativeTypeNum::Bytes, false, bytes(v)?),
+        Value::Timestamp(ref v) => (
+            ODPINativeTypeNum::Timestamp,
+            false,
+            ODPIDataBuffer {
+                as_timestamp: timestamp(v)?,
+            },
+        ),
+        Value::IntervalDS(ref v) => (
+            ODPINativeTypeNum::IntervalDS,
+            false,
+            ODPIDataBuffer {
+                as_interval_ds: interval_ds(v)?,
+            },
+        ),
+        Value::IntervalYM(ref v) => (
+            ODPINativeTypeNum::IntervalYM,
+            false,
+            ODPIDataBuffer {
+                as_interval_ym: ODPIIntervalYM {
+                    years: *v.years(),
+                    months: *v.months(),
+                },
+            },
+        ),
+        ref other => {
+            return Err(ErrorKind::Serialize(format!(
+                "a {} value cannot be bound to '{}'",
+                other.kind(),
+                name
+            ))
+            .into())
+        }
+    };
+
+    let mut data = ODPIData {
+        is_null: if is_null { 1 } else { 0 },
+        value: buffer,
+    };
+    stmt.bind_value_by_name(
+        name,
+        native_type,
+        &TryFrom::try_from(&mut data as *mut ODPIData)?,
+    )
+}
+
+/// Create a buffer pointing at a byte string.
+fn bytes(value: &[u8]) -> Result<ODPIDataBuffer> {
+    Ok(ODPIDataBuffer {
+        as_bytes: ODPIBytes {
+            ptr: value.as_ptr() as *mut c_char,
+            length: TryFrom::try_from(value.len())?,
+            encoding: ENCODING.as_ptr() as *const c_char,
+        },
+    })
+}
+
+/// Convert a date and time into an ODPI-C timestamp.
+fn timestamp(value: &DateTime<Utc>) -> Result<ODPITimestamp> {
+    let mut timestamp: ODPITimestamp = Default::default();
+    timestamp.year = TryFrom::try_from(value.year())?;
+    timestamp.month = TryFrom::try_from(value.month())?;
+    timestamp.day = TryFrom::try_from(value.day())?;
+    timestamp.hour = TryFrom::try_from(value.hour())?;
+    timestamp.minute = TryFrom::try_from(value.minute())?;
+    timestamp.second = TryFrom::try_from(value.second())?;
+    timestamp.fsecond = value.nanosecond();
+    Ok(timestamp)
+}
+
+/// Convert a duration into an ODPI-C day to second interval.
+fn interval_ds(value: &Duration) -> Result<ODPIIntervalDS> {
+    let nanos = (*value - Duration::seconds(value.num_seconds()))
+        .num_nanoseconds()
+        .unwrap_or(0);
+
+    Ok(ODPIIntervalDS {
+        days: TryFrom::try_from(value.num_days())?,
+        hours: TryFrom::try_from(value.num_hours() % 24)?,
+        minutes: TryFrom::try_from(value.num_minutes() % 60)?,
+        seconds: TryFrom::try_from(value.num_seconds() % 60)?,
+        fseconds: TryFrom::try_from(nanos)?,
+    })
+}
+
+/// Create the error returned when something other than a struct or map is bound.
+fn not_fields() -> Error {
+    ErrorKind::Serialize("only a struct or a map can be bound".to_string()).into()
+}
+
+/// Create the error returned when a field value is not a scalar.
+fn not_scalar() -> Error {
+    ErrorKind::Serialize("only scalar values can be bound".to_string()).into()
+}
+
+/// Serializes a struct or map into the name and value of each field.
+struct FieldsSerializer;
+
+/// Collects the fields of a struct or map.
+struct Fields {
+    /// The name and value of each field.
+    fields: Vec<(String, Value)>,
+    /// The name of the map entry whose value is serialized next.
+    key: Option<String>,
+}
+
+impl ser::Serializer for FieldsSerializer {
+    type Ok = Vec<(String, Value)>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Error>;
+    type SerializeTuple = Impossible<Self::Ok, Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Error>;
+    type SerializeMap = Fields;
+    type SerializeStruct = Fields;
+    type SerializeStructVariant = Impossible<Self::Ok, Error>;
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Fields> {
+        Ok(Fields {
+            fields: Vec::with_capacity(len),
+            key: None,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Fields> {
+        Ok(Fields {
+            fields: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok> {
+        Err(not_fields())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(not_fields())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(not_fields())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(not_fields())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(not_fields())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(not_fields())
+    }
+}
+
+impl ser::SerializeStruct for Fields {
+    type Ok = Vec<(String, Value)>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        let value = value.serialize(ValueSerializer)?;
+        self.fields.push((key.to_string(), value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.fields)
+    }
+}
+
+impl ser::SerializeMap for Fields {
+    type Ok = Vec<(String, Value)>;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        match key.serialize(ValueSerializer)? {
+            Value::String(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(ErrorKind::Serialize("map keys must be strings".to_string()).into()),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self.key.take().ok_or_else(|| {
+            Error::from(ErrorKind::Serialize(
+                "map value serialized before its key".to_string(),
+            ))
+        })?;
+        let value = value.serialize(ValueSerializer)?;
+        self.fields.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.fields)
+    }
+}
+
+/// Serializes a scalar into a `Value`.
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = Impossible<Value, Error>;
+    type SerializeTuple = Impossible<Value, Error>;
+    type SerializeTupleStruct = Impossible<Value, Error>;
+    type SerializeTupleVariant = Impossible<Value, Error>;
+    type SerializeMap = Impossible<Value, Error>;
+    type SerializeStruct = Impossible<Value, Error>;
+    type SerializeStructVariant = Impossible<Value, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value> {
+        Ok(Value::Int64(i64::from(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value> {
+        Ok(Value::Int64(i64::from(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value> {
+        Ok(Value::Int64(i64::from(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value> {
+        Ok(Value::Int64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value> {
+        Ok(Value::Int64(i64::from(v)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value> {
+        Ok(Value::Int64(i64::from(v)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value> {
+        Ok(Value::Int64(i64::from(v)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value> {
+        Ok(Value::Uint64(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value> {
+        Ok(Value::Float(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value> {
+        Ok(Value::Double(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        match value.serialize(self)? {
+            Value::String(ref v) if name == TIMESTAMP => DateTime::parse_from_rfc3339(v)
+                .map(|timestamp| Value::Timestamp(timestamp.with_timezone(&Utc)))
+                .map_err(|e| ErrorKind::Serialize(e.to_string()).into()),
+            value => Ok(value),
+        }
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Value> {
+        Err(not_scalar())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(not_scalar())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(not_scalar())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(not_scalar())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(not_scalar())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(not_scalar())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(not_scalar())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(not_scalar())
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `DateTime<Utc>` fields are bound as timestamps by `Statement::bind_struct()` when they are
+//! annotated with `#[serde(with = "mimir::serde::timestamp")]`, or
+//! `#[serde(with = "mimir::serde::timestamp::option")]` for `Option<DateTime<Utc>>`. Without the
+//! annotation chrono serializes the field as a string, which is bound as a string. Other
+//! serializers see the RFC 3339 string chrono would have produced.
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_crate::de::{Deserialize, Deserializer};
+use serde_crate::ser::{Serialize, Serializer};
+
+/// The name of the newtype struct that marks a string as a timestamp.
+#[doc(hidden)]
+pub const TIMESTAMP: &str = "$mimir::Timestamp";
+
+/// A timestamp serialized as a marked RFC 3339 string.
+struct Marked<'a>(&'a DateTime<Utc>);
+
+impl<'a> Serialize for Marked<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(
+            TIMESTAMP,
+            &self.0.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        )
+    }
+}
+
+/// Serialize a `DateTime<Utc>` so that it is bound as a timestamp.
+pub fn serialize<S: Serializer>(value: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+    Marked(value).serialize(serializer)
+}
+
+/// Deserialize a `DateTime<Utc>` from an RFC 3339 string.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    DateTime::<Utc>::deserialize(deserializer)
+}
+
+/// The same conversions for `Option<DateTime<Utc>>`, where `None` is bound as a null.
+pub mod option {
+    use super::Marked;
+    use chrono::{DateTime, Utc};
+    use serde_crate::de::{Deserialize, Deserializer};
+    use serde_crate::ser::Serializer;
+
+    /// Serialize an `Option<DateTime<Utc>>` so that it is bound as a timestamp or a null.
+    pub fn serialize<S: Serializer>(
+        value: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match *value {
+            Some(ref value) => serializer.serialize_some(&Marked(value)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserialize an `Option<DateTime<Utc>>` from an RFC 3339 string or a null.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Option::<DateTime<Utc>>::deserialize(deserializer)
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The result of executing a statement for an array of rows with `Statement::execute_batch()`.
+//! Each failure is reported with the index of the row that raised it, rather than the `offset` of
+//! the batch error.
+use common::error;
+
+/// The maximum number of rows in a batch. The row of a batch error is reported as a 16-bit
+/// offset, so larger batches cannot be executed with batch errors enabled.
+#[doc(hidden)]
+pub const MAX_BATCH_ROWS: u32 = 65_536;
+
+/// The outcome of executing a statement for an array of rows with batch errors enabled.
+#[derive(Clone, Debug, Default, Eq, Getters, PartialEq)]
+pub struct BatchResult {
+    /// The indices of the rows that were executed successfully, in ascending order.
+    #[get = "pub"]
+    successes: Vec<usize>,
+    /// The number of rows affected by each row of the batch. Rows that failed affect no rows.
+    #[get = "pub"]
+    row_counts: Vec<u64>,
+    /// The index of each row that failed, along with its error, in ascending order.
+    #[get = "pub"]
+    failures: Vec<(usize, error::Info)>,
+}
+
+impl BatchResult {
+    /// Create a new `BatchResult` from the row counts and batch errors of an execution of
+    /// `num_rows` rows.
+    #[doc(hidden)]
+    pub fn new(num_rows: usize, row_counts: Vec<u64>, errors: Vec<error::Info>) -> Self {
+        let mut row_counts = row_counts;
+        row_counts.resize(num_rows, 0);
+
+        let mut failures = errors
+            .into_iter()
+            .map(|info| (info.offset() as usize, info))
+            .filter(|&(row, _)| row < num_rows)
+            .collect::<Vec<(usize, error::Info)>>();
+        failures.sort_by_key(|&(row, _)| row);
+
+        for &(row, _) in &failures {
+            row_counts[row] = 0;
+        }
+
+        let successes = (0..num_rows)
+            .filter(|row| {
+                failures
+                    .binary_search_by_key(row, |&(failed, _)| failed)
+                    .is_err()
+            })
+            .collect();
+
+        Self {
+            successes,
+            row_counts,
+            failures,
+        }
+    }
+
+    /// Get the number of rows in the batch.
+    pub fn len(&self) -> usize {
+        self.row_counts.len()
+    }
+
+    /// Returns true if the batch had no rows.
+    pub fn is_empty(&self) -> bool {
+        self.row_counts.is_empty()
+    }
+
+    /// Returns true if every row in the batch was executed successfully.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Get the total number of rows affected by the batch.
+    pub fn total_row_count(&self) -> u64 {
+        self.row_counts.iter().sum()
+    }
+}
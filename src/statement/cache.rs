@@ -0,0 +1,178 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Cached statements are prepared with `Connection::prepare_cached()`, which uses the SQL text as
+//! the tag of the statement in the OCI statement cache. When a `CachedStatement` is dropped, the
+//! statement is closed with that tag so that it is returned to the cache. mimir mirrors the
+//! contents of the OCI cache in a least recently used list, sized to match the statement cache
+//! size of the connection, in order to count cache hits, misses and evictions.
+use connection::Connection;
+use statement::Statement;
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+
+/// A statement prepared with `Connection::prepare_cached()`. The statement is returned to the
+/// statement cache when it is dropped.
+pub struct CachedStatement<'conn> {
+    /// The connection the statement was prepared on.
+    conn: &'conn Connection,
+    /// The SQL text, used as the statement cache tag.
+    sql: String,
+    /// The prepared statement.
+    stmt: Statement,
+}
+
+impl<'conn> CachedStatement<'conn> {
+    /// Create a new `CachedStatement`.
+    #[doc(hidden)]
+    pub fn new(conn: &'conn Connection, sql: &str, stmt: Statement) -> Self {
+        Self {
+            conn,
+            sql: sql.to_string(),
+            stmt,
+        }
+    }
+
+    /// Get the connection the statement was prepared on.
+    pub fn connection(&self) -> &Connection {
+        self.conn
+    }
+
+    /// Get the SQL text of the statement.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+}
+
+impl<'conn> Deref for CachedStatement<'conn> {
+    type Target = Statement;
+
+    fn deref(&self) -> &Statement {
+        &self.stmt
+    }
+}
+
+impl<'conn> DerefMut for CachedStatement<'conn> {
+    fn deref_mut(&mut self) -> &mut Statement {
+        &mut self.stmt
+    }
+}
+
+impl<'conn> Drop for CachedStatement<'conn> {
+    fn drop(&mut self) {
+        if self.stmt.close(Some(&self.sql)).is_err() {
+            self.conn.statement_cache_discard(&self.sql);
+        }
+    }
+}
+
+/// A snapshot of the statement cache counters of a connection, returned by the function
+/// `Connection::statement_cache_stats()`.
+#[derive(Clone, Copy, Debug, Default, Eq, Getters, PartialEq)]
+pub struct CacheStats {
+    /// The size of the statement cache, in number of statements.
+    #[get = "pub"]
+    capacity: u32,
+    /// The number of statements currently held in the cache.
+    #[get = "pub"]
+    size: u32,
+    /// The number of `prepare_cached()` calls that found the statement in the cache.
+    #[get = "pub"]
+    hits: u64,
+    /// The number of `prepare_cached()` calls that did not find the statement in the cache.
+    #[get = "pub"]
+    misses: u64,
+    /// The number of statements pushed out of the cache to make room for another.
+    #[get = "pub"]
+    evictions: u64,
+}
+
+impl CacheStats {
+    /// The fraction of `prepare_cached()` calls that found the statement in the cache, between 0
+    /// and 1.
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// The least recently used list of SQL texts believed to be held in the OCI statement cache.
+#[derive(Clone, Debug, Default)]
+#[doc(hidden)]
+pub struct Lru {
+    /// The size of the statement cache, or None if it has not been read from the connection yet.
+    capacity: Option<u32>,
+    /// The cached SQL texts, most recently used first.
+    entries: VecDeque<String>,
+    /// The number of cache hits.
+    hits: u64,
+    /// The number of cache misses.
+    misses: u64,
+    /// The number of evictions.
+    evictions: u64,
+}
+
+impl Lru {
+    /// Get the capacity, if it is known.
+    pub fn capacity(&self) -> Option<u32> {
+        self.capacity
+    }
+
+    /// Set the capacity, evicting the least recently used entries that no longer fit.
+    pub fn set_capacity(&mut self, capacity: u32) {
+        self.capacity = Some(capacity);
+        self.trim();
+    }
+
+    /// Record a lookup of the given SQL text, returning true on a hit.
+    pub fn lookup(&mut self, sql: &str) -> bool {
+        if let Some(idx) = self.entries.iter().position(|entry| entry == sql) {
+            let entry = self.entries.remove(idx).expect("invalid index");
+            self.entries.push_front(entry);
+            self.hits += 1;
+            true
+        } else {
+            self.misses += 1;
+            if self.capacity.map_or(true, |capacity| capacity > 0) {
+                self.entries.push_front(sql.to_string());
+                self.trim();
+            }
+            false
+        }
+    }
+
+    /// Forget the given SQL text, without counting an eviction.
+    pub fn discard(&mut self, sql: &str) {
+        self.entries.retain(|entry| entry != sql);
+    }
+
+    /// Get a snapshot of the counters.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            capacity: self.capacity.unwrap_or(0),
+            size: self.entries.len() as u32,
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+        }
+    }
+
+    /// Evict the least recently used entries beyond the capacity.
+    fn trim(&mut self) {
+        if let Some(capacity) = self.capacity {
+            while self.entries.len() > capacity as usize {
+                self.entries.pop_back();
+                self.evictions += 1;
+            }
+        }
+    }
+}
@@ -15,16 +15,24 @@
 use common::error;
 use data::Data;
 use error::{ErrorKind, Result};
-use odpi::flags::ODPIExecMode;
+use odpi::flags::{self, ODPIExecMode};
 use odpi::opaque::ODPIStmt;
-use odpi::structs::{ODPIQueryInfo, ODPIStmtInfo};
+use odpi::structs::{ODPIErrorInfo, ODPIQueryInfo, ODPIStmtInfo};
 use odpi::{enums, externs};
 use query;
+use query::column::Column;
+use serde;
+use serde_crate::ser::Serialize;
+use statement::batch::{BatchResult, MAX_BATCH_ROWS};
 use std::convert::TryFrom;
-use std::{ptr, slice};
+use std::{fmt, ptr, slice};
 use util::ODPIStr;
 use variable::Var;
 
+pub mod batch;
+pub mod cache;
+pub mod returning;
+
 /// This structure represents statements of all types (queries, DML, DLL and PL/SQL) and is
 /// available by handle to a calling application or driver.
 pub struct Statement {
@@ -136,6 +144,16 @@ impl Statement {
         )
     }
 
+    /// Binds each field of a struct to the named placeholder with the same name, ignoring case.
+    /// The struct is serialized with serde, and each value is bound as with
+    /// `bind_value_by_name()`. It is an error for a placeholder to have no field or for a field to
+    /// have no placeholder. See the `serde` module for how field values are converted.
+    ///
+    /// * `value` - the struct (or map with string keys) to bind.
+    pub fn bind_struct<T: Serialize>(&self, value: &T) -> Result<()> {
+        serde::bind_struct(self, value)
+    }
+
     /// Closes the statement and makes it unusable for further work immediately, rather than when
     /// the reference count reaches zero.
     ///
@@ -152,13 +170,37 @@ impl Statement {
         )
     }
 
-    // /// Defines the variable that will be used to fetch rows from the statement. A reference to
-    //the
-    // /// variable will be retained until the next define is performed on the same position or the
-    // /// statement is closed.
-    // pub fn define(&self, pos: u32, var: &mut Var) -> Result<()> {
-    //     Ok(())
-    // }
+    /// Returns a description of each column of the query. The statement must have been executed.
+    pub fn columns(&self) -> Result<Vec<Column>> {
+        let num_cols = self.get_num_query_columns()?;
+        let mut columns = Vec::with_capacity(num_cols as usize);
+
+        for pos in 1..=num_cols {
+            let info = self.get_query_info(pos)?;
+            columns.push(Column::new(
+                &info.name(),
+                &info.type_info(),
+                info.null_ok(),
+            )?);
+        }
+
+        Ok(columns)
+    }
+
+    /// Defines the variable that will be used to fetch rows from the statement. A reference to the
+    /// variable will be retained until the next define is performed on the same position or the
+    /// statement is closed.
+    ///
+    /// * `pos` - the position of the column to define. The first position is 1.
+    /// * `var` - the variable the column is fetched into. Its array size must be at least the fetch
+    /// array size of the statement.
+    pub fn define(&self, pos: u32, var: &Var) -> Result<()> {
+        try_dpi!(
+            externs::dpiStmt_define(self.inner, pos, var.inner()),
+            Ok(()),
+            ErrorKind::Statement("dpiStmt_define".to_string())
+        )
+    }
 
     /// Defines the type of data that will be used to fetch rows from the statement. This is
     /// intended for use with the function `Statement::get_query_value()`, when the default
@@ -211,6 +253,35 @@ impl Statement {
         )
     }
 
+    /// Executes the statement the specified number of times using the bound values, with batch
+    /// errors and array DML row counts enabled. Rows that fail do not stop the rest of the batch
+    /// from being executed, and are reported in the returned `BatchResult` along with the index of
+    /// the row. Batch errors and row counts are only available when both the client and the server
+    /// are at 12.1.
+    ///
+    /// * `num_rows` - the number of times the statement is executed, at most 65536. Each row
+    /// corresponds to one of the elements of the arrays that were bound earlier.
+    pub fn execute_batch(&self, num_rows: u32) -> Result<BatchResult> {
+        if num_rows > MAX_BATCH_ROWS {
+            return Err(ErrorKind::ArraySize(num_rows).into());
+        }
+
+        self.execute_many(
+            flags::DPI_MODE_EXEC_BATCH_ERRORS | flags::DPI_MODE_EXEC_ARRAY_DML_ROWCOUNTS,
+            num_rows,
+        )?;
+
+        let row_counts = self.get_row_counts()?;
+        let num_errors = self.get_batch_error_count()?;
+        let errors = if num_errors > 0 {
+            self.get_batch_errors(num_errors)?
+        } else {
+            Vec::new()
+        };
+
+        Ok(BatchResult::new(num_rows as usize, row_counts, errors))
+    }
+
     /// Executes the statement the specified number of times using the bound values. Each bound
     /// variable must have at least this many elements allocated or an error is returned.
     ///
@@ -287,16 +358,11 @@ impl Statement {
     /// * `num_errors` - the size of the errors array in number of elements. The number of batch
     /// errors that are available can be determined using `get_batch_error_count()`.
     pub fn get_batch_errors(&self, num_errors: u32) -> Result<Vec<error::Info>> {
-        let err_ptr = ptr::null_mut();
+        let mut errors = vec![ODPIErrorInfo::default(); num_errors as usize];
 
         try_dpi!(
-            externs::dpiStmt_getBatchErrors(self.inner, num_errors, err_ptr),
-            {
-                let err_slice = unsafe { slice::from_raw_parts(err_ptr, num_errors as usize) };
-                let odpi_vec = Vec::from(err_slice);
-                let res_vec = odpi_vec.iter().map(|x| (*x).into()).collect();
-                Ok(res_vec)
-            },
+            externs::dpiStmt_getBatchErrors(self.inner, num_errors, errors.as_mut_ptr()),
+            Ok(errors.into_iter().map(|x| x.into()).collect()),
             ErrorKind::Statement("dpiStmt_getBatchErrors".to_string())
         )
     }
@@ -331,16 +397,12 @@ impl Statement {
                 names_len_vec.as_mut_ptr()
             ),
             {
-                let mut res = Vec::new();
-
-                for (idx, (name, name_len)) in
-                    names_vec.iter().zip(names_len_vec.iter()).enumerate()
-                {
-                    if idx <= actual_num_bind_names as usize {
-                        let name_s = ODPIStr::new(*name, *name_len);
-                        res.push(name_s.into());
-                    }
-                }
+                let res = names_vec
+                    .iter()
+                    .zip(names_len_vec.iter())
+                    .take(actual_num_bind_names as usize)
+                    .map(|(name, name_len)| ODPIStr::new(*name, *name_len).into())
+                    .collect();
 
                 Ok(res)
             },
@@ -427,7 +489,22 @@ impl Statement {
     /// with the array DML rowcounts mode enabled. This feature is only available if both client and
     /// server are at 12.1.
     pub fn get_row_counts(&self) -> Result<Vec<u64>> {
-        Err(ErrorKind::Statement("Not Implemented!".to_string()).into())
+        let mut num_row_counts = 0;
+        let mut row_counts_ptr = ptr::null_mut();
+
+        try_dpi!(
+            externs::dpiStmt_getRowCounts(self.inner, &mut num_row_counts, &mut row_counts_ptr),
+            {
+                if row_counts_ptr.is_null() || num_row_counts == 0 {
+                    Ok(Vec::new())
+                } else {
+                    let row_counts =
+                        unsafe { slice::from_raw_parts(row_counts_ptr, num_row_counts as usize) };
+                    Ok(row_counts.to_vec())
+                }
+            },
+            ErrorKind::Statement("dpiStmt_getRowCounts".to_string())
+        )
     }
 
     /// Returns the id of the query that was just registered on the subscription by calling
@@ -462,8 +539,12 @@ impl Statement {
     /// network round trips are required to fetch rows from the database but more memory is also
     /// required. A value of zero will reset the array size to the default value of
     /// DPI_DEFAULT_FETCH_ARRAY_SIZE.
-    pub fn set_fetch_array_size(&self, _array_size: u32) -> Result<()> {
-        Err(ErrorKind::Statement("Not Implemented!".to_string()).into())
+    pub fn set_fetch_array_size(&self, array_size: u32) -> Result<()> {
+        try_dpi!(
+            externs::dpiStmt_setFetchArraySize(self.inner, array_size),
+            Ok(()),
+            ErrorKind::Statement("dpiStmt_setFetchArraySize".to_string())
+        )
     }
 }
 
@@ -473,6 +554,32 @@ impl From<*mut ODPIStmt> for Statement {
     }
 }
 
+/// Cloning a statement adds a reference to the ODPI-C statement, which is released when the clone
+/// is dropped.
+impl Clone for Statement {
+    fn clone(&self) -> Self {
+        if !self.inner.is_null() {
+            unsafe {
+                externs::dpiStmt_addRef(self.inner);
+            }
+        }
+        Self { inner: self.inner }
+    }
+}
+
+impl fmt::Debug for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Statement {{ inner: {:?} }}", self.inner)
+    }
+}
+
+/// Statements are equal if they refer to the same ODPI-C statement.
+impl PartialEq for Statement {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
 impl Drop for Statement {
     fn drop(&mut self) {
         if !self.inner.is_null() {
@@ -0,0 +1,136 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! DML returning statements (`INSERT ... RETURNING ... INTO ...`) are executed with
+//! `Connection::execute_returning()` and `Connection::execute_many_returning()`. The parameters are
+//! bound by position to the first placeholders of the statement and one output variable is
+//! created for each of the remaining placeholders, which must be those of the `INTO` clause. Once
+//! the statement has executed, the values returned for each iteration are read with
+//! `Var::get_returned_data()`, one `Row` per affected row.
+//!
+//! Unlike the columns of a query, the placeholders of the `INTO` clause cannot be described
+//! before the statement is executed, so their types are given by the caller. The database converts
+//! the returned values to these types where it can: a NUMBER column can be returned into a
+//! `SqlType::Varchar`, but returning text that is not a number into a `SqlType::Int64` makes the
+//! statement fail.
+use connection::Connection;
+use error::{ErrorKind, Result};
+use odpi::flags;
+use row::Row;
+use types::{SqlType, ToSql, Value};
+use util::PrivateTryFromUsize;
+use variable::builder::VarBuilder;
+use variable::Var;
+
+/// Execute a DML returning statement once for each set of parameters, returning the rows returned
+/// by each iteration.
+///
+/// * `conn` - the connection to execute the statement on.
+/// * `sql` - the DML returning statement.
+/// * `params` - the parameters of each iteration, one value per input placeholder.
+/// * `returning` - the type of each placeholder of the `INTO` clause.
+#[doc(hidden)]
+pub fn execute(
+    conn: &Connection,
+    sql: &str,
+    params: &[&[&dyn ToSql]],
+    returning: &[SqlType],
+) -> Result<Vec<Vec<Row>>> {
+    if params.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let num_params = params[0].len();
+    if let Some(row) = params.iter().find(|row| row.len() != num_params) {
+        return Err(ErrorKind::ColumnCount(num_params, row.len()).into());
+    }
+
+    let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+    let num_binds = stmt.get_bind_count()? as usize;
+    if num_binds != num_params + returning.len() {
+        return Err(ErrorKind::ColumnCount(num_binds, num_params + returning.len()).into());
+    }
+
+    let num_iters = u32::private_try_from(params.len())?;
+    let mut pos = 1;
+
+    for col in 0..num_params {
+        let sql_type = params
+            .iter()
+            .map(|row| row[col].sql_type())
+            .fold(params[0][col].sql_type(), column_type);
+        let var = new_var(conn, sql_type, num_iters)?;
+        for (iter, row) in params.iter().enumerate() {
+            var.set(u32::private_try_from(iter)?, row[col])?;
+        }
+        stmt.bind_by_pos(pos, &var)?;
+        pos += 1;
+    }
+
+    let mut vars = Vec::with_capacity(returning.len());
+    for sql_type in returning {
+        let var = new_var(conn, *sql_type, num_iters)?;
+        stmt.bind_by_pos(pos, &var)?;
+        pos += 1;
+        vars.push(var);
+    }
+
+    if num_iters == 1 {
+        stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    } else {
+        stmt.execute_many(flags::DPI_MODE_EXEC_DEFAULT, num_iters)?;
+    }
+
+    (0..num_iters)
+        .map(|iter| returned_rows(&vars, returning, iter))
+        .collect()
+}
+
+/// Read the rows returned by the given iteration.
+fn returned_rows(vars: &[Var], returning: &[SqlType], iter: u32) -> Result<Vec<Row>> {
+    let mut columns = Vec::with_capacity(vars.len());
+    for (var, sql_type) in vars.iter().zip(returning) {
+        let values = var
+            .get_returned_data(iter)?
+            .iter()
+            .map(|data| {
+                Value::from_data(data, sql_type.oracle_type_num(), sql_type.native_type_num())
+            })
+            .collect::<Result<Vec<Value>>>()?;
+        columns.push(values);
+    }
+
+    let num_rows = columns.iter().map(|values| values.len()).min().unwrap_or(0);
+    let mut rows = (0..num_rows)
+        .map(|_| Vec::with_capacity(columns.len()))
+        .collect::<Vec<Vec<Value>>>();
+    for values in columns {
+        for (row, value) in rows.iter_mut().zip(values) {
+            row.push(value);
+        }
+    }
+
+    Ok(rows.into_iter().map(Row::new).collect())
+}
+
+/// Create a variable holding one value per iteration.
+fn new_var(conn: &Connection, sql_type: SqlType, num_iters: u32) -> Result<Var> {
+    VarBuilder::default()
+        .sql_type(sql_type)
+        .max_array_size(num_iters)
+        .create(conn)
+}
+
+/// Pick the type of an input column from the types of its values. A VARCHAR2 of one character,
+/// which is also the type of a null value, gives way to any other type.
+fn column_type(acc: SqlType, sql_type: SqlType) -> SqlType {
+    match acc {
+        SqlType::Varchar(1) => sql_type.widest(acc),
+        _ => acc.widest(sql_type),
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Transaction handles group advanced queuing operations and DML performed on a connection so that
+//! they are committed or rolled back as a unit. They are created by calling the function
+//! `Connection::begin_transaction()` and are finished by calling `commit()` or `rollback()`. A
+//! transaction that is dropped without being finished is rolled back.
+use connection::Connection;
+use dequeue;
+use enqueue;
+use error::{ErrorKind, Result};
+use message::Properties;
+use object::Object;
+use odpi::enums::ODPIVisibility;
+
+/// This structure represents a local transaction on a connection. Messages enqueued or dequeued
+/// through a transaction must use `ODPIVisibility::OnCommit`, so that they only become visible (or
+/// are only removed from the queue) when the transaction is committed.
+pub struct Transaction<'conn> {
+    /// The connection the transaction is running on.
+    conn: &'conn Connection,
+    /// Has the transaction been committed or rolled back?
+    finished: bool,
+}
+
+impl<'conn> Transaction<'conn> {
+    /// Create a new `Transaction` on the given connection.
+    #[doc(hidden)]
+    pub fn new(conn: &'conn Connection) -> Self {
+        Self {
+            conn,
+            finished: false,
+        }
+    }
+
+    /// Get the connection the transaction is running on. Any statements executed on this
+    /// connection before the transaction is finished are part of the transaction.
+    pub fn connection(&self) -> &Connection {
+        self.conn
+    }
+
+    /// Commits the transaction, making any enqueued messages visible and permanently removing any
+    /// dequeued messages from their queues.
+    pub fn commit(mut self) -> Result<()> {
+        self.finished = true;
+        self.conn.commit()
+    }
+
+    /// Dequeues a message from a queue as part of the transaction. If the transaction is rolled
+    /// back, the message is returned to the queue and its number of attempts is incremented.
+    ///
+    /// * `queue_name` - the name of the queue from which the message is to be dequeued.
+    /// * `options` - the dequeue options, which must use `ODPIVisibility::OnCommit`.
+    /// * `props` - the message properties that will be populated with information from the
+    /// message that is dequeued.
+    /// * `payload` - the object the payload of the message is read into.
+    pub fn deque_object(
+        &self,
+        queue_name: &str,
+        options: &dequeue::Options,
+        props: &Properties,
+        payload: &Object,
+    ) -> Result<String> {
+        check_visibility(options.get_visibility()?)?;
+        self.conn.deque_object(queue_name, options, props, payload)
+    }
+
+    /// Enqueues a message to a queue as part of the transaction. The message is not visible to
+    /// consumers until the transaction is committed.
+    ///
+    /// * `queue_name` - the name of the queue to which the message is to be enqueued.
+    /// * `options` - the enqueue options, which must use `ODPIVisibility::OnCommit`.
+    /// * `props` - the message properties that will affect the message that is enqueued.
+    /// * `payload` - the payload of the message.
+    pub fn enqueue_object(
+        &self,
+        queue_name: &str,
+        options: &enqueue::Options,
+        props: &Properties,
+        payload: &Object,
+    ) -> Result<String> {
+        check_visibility(options.get_visibility()?)?;
+        self.conn
+            .enqueue_object(queue_name, options, props, payload)
+    }
+
+    /// Dequeues a message and runs the given function to process it. If the function succeeds the
+    /// transaction is committed, otherwise it is rolled back, which returns the message to the
+    /// queue for redelivery. Once the number of attempts reaches the `max_retries` of the queue,
+    /// Oracle moves the message to the exception queue.
+    ///
+    /// The function is handed the transaction, the message id, the payload (read into `payload`)
+    /// and the message properties. `Properties::get_num_attempts()` can be used to detect poison
+    /// messages before doing any work.
+    pub fn process<F>(
+        self,
+        queue_name: &str,
+        options: &dequeue::Options,
+        props: &Properties,
+        payload: &Object,
+        f: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&Self, &str, &Object, &Properties) -> Result<()>,
+    {
+        let msg_id = self.deque_object(queue_name, options, props, payload)?;
+
+        match f(&self, &msg_id, payload, props) {
+            Ok(_) => self.commit(),
+            Err(e) => {
+                self.rollback()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Rolls back the transaction. Dequeued messages are returned to their queues and enqueued
+    /// messages are discarded.
+    pub fn rollback(mut self) -> Result<()> {
+        self.finished = true;
+        self.conn.rollback()
+    }
+}
+
+impl<'conn> Drop for Transaction<'conn> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.conn.rollback();
+        }
+    }
+}
+
+/// Queue operations inside a transaction must be part of it.
+fn check_visibility(visibility: ODPIVisibility) -> Result<()> {
+    match visibility {
+        ODPIVisibility::OnCommit => Ok(()),
+        ODPIVisibility::Immediate => Err(ErrorKind::Visibility.into()),
+    }
+}
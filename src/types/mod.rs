@@ -0,0 +1,930 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Conversions between Rust values and the data stored in variables. A `SqlType` describes the
+//! Oracle and native types (and buffer size) of a variable, and a `ToSql` value writes itself into a
+//! position of a variable created for a given `SqlType`, converting between numeric types where
+//! it is lossless or expected. In the other direction, data is read into a `Value`, which a
+//! `FromSql` type is created from.
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use data::YearsMonths;
+use error::{Error, ErrorKind, Result};
+use lob::Lob;
+use object::Object;
+use objectattr::ObjectAttr;
+use objecttype::ObjectType;
+use odpi::enums::{ODPINativeTypeNum, ODPIOracleTypeNum};
+use odpi::externs;
+use odpi::structs::{ODPIData, ODPITimestamp};
+use rowid::Rowid;
+use statement::Statement;
+use std::convert::TryFrom;
+use std::os::raw::c_char;
+use std::slice;
+use util::{ODPIStr, PrivateTryFromUsize};
+use variable::Var;
+
+/// The type of a variable used to bind or fetch values.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SqlType {
+    /// A PL/SQL BOOLEAN.
+    Boolean,
+    /// A REF CURSOR.
+    Cursor,
+    /// A DATE.
+    Date,
+    /// A NUMBER, transferred as a double precision floating point number.
+    Double,
+    /// A NUMBER, transferred as a 64-bit integer.
+    Int64,
+    /// An NVARCHAR2 with the given maximum size, in characters.
+    NVarchar(u32),
+    /// A RAW with the given maximum size, in bytes.
+    Raw(u32),
+    /// A TIMESTAMP.
+    Timestamp,
+    /// A NUMBER, transferred as an unsigned 64-bit integer.
+    Uint64,
+    /// A VARCHAR2 with the given maximum size, in characters.
+    Varchar(u32),
+}
+
+impl SqlType {
+    /// Get the Oracle type used for variables of this type.
+    pub fn oracle_type_num(&self) -> ODPIOracleTypeNum {
+        match *self {
+            SqlType::Boolean => ODPIOracleTypeNum::Boolean,
+            SqlType::Cursor => ODPIOracleTypeNum::Stmt,
+            SqlType::Date => ODPIOracleTypeNum::Date,
+            SqlType::Double | SqlType::Int64 | SqlType::Uint64 => ODPIOracleTypeNum::Number,
+            SqlType::NVarchar(_) => ODPIOracleTypeNum::NVarchar,
+            SqlType::Raw(_) => ODPIOracleTypeNum::Raw,
+            SqlType::Timestamp => ODPIOracleTypeNum::Timestamp,
+            SqlType::Varchar(_) => ODPIOracleTypeNum::Varchar,
+        }
+    }
+
+    /// Get the native type used for variables of this type.
+    pub fn native_type_num(&self) -> ODPINativeTypeNum {
+        match *self {
+            SqlType::Boolean => ODPINativeTypeNum::Boolean,
+            SqlType::Cursor => ODPINativeTypeNum::Stmt,
+            SqlType::Date | SqlType::Timestamp => ODPINativeTypeNum::Timestamp,
+            SqlType::Double => ODPINativeTypeNum::Double,
+            SqlType::Int64 => ODPINativeTypeNum::Int64,
+            SqlType::NVarchar(_) | SqlType::Raw(_) | SqlType::Varchar(_) => {
+                ODPINativeTypeNum::Bytes
+            }
+            SqlType::Uint64 => ODPINativeTypeNum::Uint64,
+        }
+    }
+
+    /// Get the size of the buffer used for each element of variables of this type. This is only
+    /// used for types transferred as byte strings, and is 0 for all other types.
+    pub fn size(&self) -> u32 {
+        match *self {
+            SqlType::NVarchar(size) | SqlType::Raw(size) | SqlType::Varchar(size) => size,
+            _ => 0,
+        }
+    }
+
+    /// Returns true if `size()` is in bytes rather than characters.
+    pub fn size_is_bytes(&self) -> bool {
+        match *self {
+            SqlType::Raw(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Pick the wider of two types of the same kind, so that byte string buffers are large enough
+    /// for the values of both. A type of another kind (such as the type of a null value) is
+    /// ignored.
+    #[doc(hidden)]
+    pub fn widest(self, other: Self) -> Self {
+        match (self, other) {
+            (SqlType::NVarchar(a), SqlType::NVarchar(b)) => SqlType::NVarchar(a.max(b)),
+            (SqlType::Raw(a), SqlType::Raw(b)) => SqlType::Raw(a.max(b)),
+            (SqlType::Varchar(a), SqlType::Varchar(b)) => SqlType::Varchar(a.max(b)),
+            (sql_type, _) => sql_type,
+        }
+    }
+}
+
+/// The size, in characters, of VARCHAR2 variables created for values of an unknown size.
+const DEFAULT_VARCHAR_SIZE: u32 = 4000;
+
+/// The size, in bytes, of RAW variables created for values of an unknown size.
+const DEFAULT_RAW_SIZE: u32 = 2000;
+
+/// 2^63, the smallest double above the range of an `i64`. Its negation is `i64::MIN`.
+const TWO_POW_63: f64 = 9_223_372_036_854_775_808.0;
+
+/// A value that can be written into a variable to be bound to a statement.
+pub trait ToSql {
+    /// Get the type of the variable used for values of this type when no value is available, as
+    /// when creating a variable with `Var::builder()`. Defaults to a VARCHAR2 of 4000 characters.
+    fn default_sql_type() -> SqlType
+    where
+        Self: Sized,
+    {
+        SqlType::Varchar(DEFAULT_VARCHAR_SIZE)
+    }
+
+    /// Get the type of the variable this value would be bound with if no other type is given.
+    fn sql_type(&self) -> SqlType;
+
+    /// Write this value into a variable.
+    ///
+    /// * `var` - the variable to write the value into.
+    /// * `pos` - the array position in the variable which is to be set. The first position is 0.
+    /// * `sql_type` - the type the variable was created with.
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()>;
+}
+
+/// A value converted for writing into a variable.
+enum Scalar<'a> {
+    /// A null value.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A signed integer.
+    Int(i64),
+    /// An unsigned integer.
+    Uint(u64),
+    /// A floating point number.
+    Double(f64),
+    /// A byte string.
+    Bytes(&'a [u8]),
+    /// A date and time.
+    Timestamp(DateTime<Utc>),
+}
+
+impl<'a> Scalar<'a> {
+    /// Describe the kind of value, for error messages.
+    fn kind(&self) -> &'static str {
+        match *self {
+            Scalar::Null => "null",
+            Scalar::Bool(_) => "boolean",
+            Scalar::Int(_) => "integer",
+            Scalar::Uint(_) => "unsigned integer",
+            Scalar::Double(_) => "floating point",
+            Scalar::Bytes(_) => "byte string",
+            Scalar::Timestamp(_) => "timestamp",
+        }
+    }
+}
+
+/// Write a converted value into position `pos` of the variable.
+fn write(var: &Var, pos: u32, sql_type: &SqlType, value: &Scalar) -> Result<()> {
+    if sql_type.native_type_num() == ODPINativeTypeNum::Bytes {
+        return match *value {
+            Scalar::Null => set_null(var, pos),
+            Scalar::Bytes(bytes) => set_bytes(var, pos, bytes),
+            Scalar::Int(v) if !sql_type.size_is_bytes() => set_bytes(var, pos, v.to_string()),
+            Scalar::Uint(v) if !sql_type.size_is_bytes() => set_bytes(var, pos, v.to_string()),
+            Scalar::Double(v) if !sql_type.size_is_bytes() => set_bytes(var, pos, v.to_string()),
+            _ => Err(mismatch(value, sql_type)),
+        };
+    }
+
+    let data = slot(var, pos)?;
+
+    match (sql_type.native_type_num(), value) {
+        (_, &Scalar::Null) => {
+            data.is_null = 1;
+            return Ok(());
+        }
+        (ODPINativeTypeNum::Boolean, &Scalar::Bool(v)) => {
+            data.value.as_boolean = if v { 1 } else { 0 }
+        }
+        (ODPINativeTypeNum::Double, &Scalar::Double(v)) => data.value.as_double = v,
+        (ODPINativeTypeNum::Double, &Scalar::Int(v)) => data.value.as_double = v as f64,
+        (ODPINativeTypeNum::Double, &Scalar::Uint(v)) => data.value.as_double = v as f64,
+        (ODPINativeTypeNum::Int64, &Scalar::Bool(v)) => {
+            data.value.as_int_64 = if v { 1 } else { 0 }
+        }
+        (ODPINativeTypeNum::Int64, &Scalar::Int(v)) => data.value.as_int_64 = v,
+        (ODPINativeTypeNum::Int64, &Scalar::Uint(v)) => {
+            data.value.as_int_64 = TryFrom::try_from(v)?
+        }
+        (ODPINativeTypeNum::Uint64, &Scalar::Int(v)) => {
+            data.value.as_uint_64 = TryFrom::try_from(v)?
+        }
+        (ODPINativeTypeNum::Uint64, &Scalar::Uint(v)) => data.value.as_uint_64 = v,
+        (ODPINativeTypeNum::Timestamp, &Scalar::Timestamp(ref v)) => {
+            let mut timestamp: ODPITimestamp = Default::default();
+            timestamp.year = TryFrom::try_from(v.year())?;
+            timestamp.month = TryFrom::try_from(v.month())?;
+            timestamp.day = TryFrom::try_from(v.day())?;
+            timestamp.hour = TryFrom::try_from(v.hour())?;
+            timestamp.minute = TryFrom::try_from(v.minute())?;
+            timestamp.second = TryFrom::try_from(v.second())?;
+            timestamp.fsecond = v.nanosecond();
+            data.value.as_timestamp = timestamp;
+        }
+        _ => return Err(mismatch(value, sql_type)),
+    }
+
+    data.is_null = 0;
+    Ok(())
+}
+
+/// Get the data at position `pos` of the variable.
+fn slot(var: &Var, pos: u32) -> Result<&mut ODPIData> {
+    match var.get_data()?.get_mut(pos as usize) {
+        Some(data) => Ok(data),
+        None => Err(ErrorKind::Var("dpiVar_getData".to_string()).into()),
+    }
+}
+
+/// Mark position `pos` of the variable as null.
+fn set_null(var: &Var, pos: u32) -> Result<()> {
+    slot(var, pos)?.is_null = 1;
+    Ok(())
+}
+
+/// Copy a byte string into position `pos` of the variable.
+fn set_bytes<T: AsRef<[u8]>>(var: &Var, pos: u32, value: T) -> Result<()> {
+    let value = value.as_ref();
+    let len = u32::private_try_from(value.len())?;
+
+    try_dpi!(
+        externs::dpiVar_setFromBytes(var.inner(), pos, value.as_ptr() as *const c_char, len),
+        Ok(()),
+        ErrorKind::Var("dpiVar_setFromBytes".to_string())
+    )
+}
+
+/// Create the error returned when a value cannot be written into a variable of the given type.
+fn mismatch(value: &Scalar, sql_type: &SqlType) -> Error {
+    ErrorKind::SqlType(value.kind().to_string(), format!("{:?}", sql_type)).into()
+}
+
+/// The size to use for a variable holding the given byte string.
+fn size_of(value: &[u8]) -> u32 {
+    u32::private_try_from(value.len())
+        .unwrap_or(u32::max_value())
+        .max(1)
+}
+
+macro_rules! int_to_sql {
+    ($($t:ty),*) => {
+        $(
+            impl ToSql for $t {
+                fn default_sql_type() -> SqlType {
+                    SqlType::Int64
+                }
+
+                fn sql_type(&self) -> SqlType {
+                    SqlType::Int64
+                }
+
+                fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+                    write(var, pos, sql_type, &Scalar::Int(i64::from(*self)))
+                }
+            }
+        )*
+    };
+}
+
+int_to_sql!(i8, i16, i32, i64, u8, u16, u32);
+
+impl ToSql for u64 {
+    fn default_sql_type() -> SqlType {
+        SqlType::Uint64
+    }
+
+    fn sql_type(&self) -> SqlType {
+        SqlType::Uint64
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        write(var, pos, sql_type, &Scalar::Uint(*self))
+    }
+}
+
+impl ToSql for bool {
+    fn default_sql_type() -> SqlType {
+        SqlType::Boolean
+    }
+
+    fn sql_type(&self) -> SqlType {
+        SqlType::Boolean
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        write(var, pos, sql_type, &Scalar::Bool(*self))
+    }
+}
+
+impl ToSql for f32 {
+    fn default_sql_type() -> SqlType {
+        SqlType::Double
+    }
+
+    fn sql_type(&self) -> SqlType {
+        SqlType::Double
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        write(var, pos, sql_type, &Scalar::Double(f64::from(*self)))
+    }
+}
+
+impl ToSql for f64 {
+    fn default_sql_type() -> SqlType {
+        SqlType::Double
+    }
+
+    fn sql_type(&self) -> SqlType {
+        SqlType::Double
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        write(var, pos, sql_type, &Scalar::Double(*self))
+    }
+}
+
+impl ToSql for str {
+    fn sql_type(&self) -> SqlType {
+        SqlType::Varchar(size_of(self.as_bytes()))
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        write(var, pos, sql_type, &Scalar::Bytes(self.as_bytes()))
+    }
+}
+
+impl ToSql for String {
+    fn default_sql_type() -> SqlType {
+        SqlType::Varchar(DEFAULT_VARCHAR_SIZE)
+    }
+
+    fn sql_type(&self) -> SqlType {
+        self.as_str().sql_type()
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        self.as_str().to_sql(var, pos, sql_type)
+    }
+}
+
+impl ToSql for [u8] {
+    fn sql_type(&self) -> SqlType {
+        SqlType::Raw(size_of(self))
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        write(var, pos, sql_type, &Scalar::Bytes(self))
+    }
+}
+
+impl ToSql for Vec<u8> {
+    fn default_sql_type() -> SqlType {
+        SqlType::Raw(DEFAULT_RAW_SIZE)
+    }
+
+    fn sql_type(&self) -> SqlType {
+        self.as_slice().sql_type()
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        self.as_slice().to_sql(var, pos, sql_type)
+    }
+}
+
+impl ToSql for DateTime<Utc> {
+    fn default_sql_type() -> SqlType {
+        SqlType::Timestamp
+    }
+
+    fn sql_type(&self) -> SqlType {
+        SqlType::Timestamp
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        write(var, pos, sql_type, &Scalar::Timestamp(*self))
+    }
+}
+
+impl ToSql for Statement {
+    fn default_sql_type() -> SqlType {
+        SqlType::Cursor
+    }
+
+    fn sql_type(&self) -> SqlType {
+        SqlType::Cursor
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        match *sql_type {
+            SqlType::Cursor => var.set_from_stmt(pos, self),
+            _ => Err(ErrorKind::SqlType("cursor".to_string(), format!("{:?}", sql_type)).into()),
+        }
+    }
+}
+
+/// `None` is written as a null. With no other type given, it is bound as a VARCHAR2, which Oracle
+/// accepts as a null for any column type.
+impl<T: ToSql> ToSql for Option<T> {
+    fn default_sql_type() -> SqlType {
+        T::default_sql_type()
+    }
+
+    fn sql_type(&self) -> SqlType {
+        match *self {
+            Some(ref value) => value.sql_type(),
+            None => SqlType::Varchar(1),
+        }
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        match *self {
+            Some(ref value) => value.to_sql(var, pos, sql_type),
+            None => write(var, pos, sql_type, &Scalar::Null),
+        }
+    }
+}
+
+impl<'a, T: ToSql> ToSql for &'a T {
+    fn default_sql_type() -> SqlType {
+        T::default_sql_type()
+    }
+
+    fn sql_type(&self) -> SqlType {
+        (**self).sql_type()
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        (**self).to_sql(var, pos, sql_type)
+    }
+}
+
+impl<'a> ToSql for &'a str {
+    fn default_sql_type() -> SqlType {
+        SqlType::Varchar(DEFAULT_VARCHAR_SIZE)
+    }
+
+    fn sql_type(&self) -> SqlType {
+        (**self).sql_type()
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        (**self).to_sql(var, pos, sql_type)
+    }
+}
+
+impl<'a> ToSql for &'a [u8] {
+    fn default_sql_type() -> SqlType {
+        SqlType::Raw(DEFAULT_RAW_SIZE)
+    }
+
+    fn sql_type(&self) -> SqlType {
+        (**self).sql_type()
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        (**self).to_sql(var, pos, sql_type)
+    }
+}
+
+impl<'a> ToSql for &'a dyn ToSql {
+    fn sql_type(&self) -> SqlType {
+        (**self).sql_type()
+    }
+
+    fn to_sql(&self, var: &Var, pos: u32, sql_type: &SqlType) -> Result<()> {
+        (**self).to_sql(var, pos, sql_type)
+    }
+}
+
+/// A value read from a variable or a query column.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A null value.
+    Null,
+    /// A boolean.
+    Boolean(bool),
+    /// A signed 64-bit integer.
+    Int64(i64),
+    /// An unsigned 64-bit integer.
+    Uint64(u64),
+    /// A single precision floating point number.
+    Float(f32),
+    /// A double precision floating point number.
+    Double(f64),
+    /// Character data.
+    String(String),
+    /// Raw binary data.
+    Bytes(Vec<u8>),
+    /// A date and time.
+    Timestamp(DateTime<Utc>),
+    /// An interval of days to seconds.
+    IntervalDS(Duration),
+    /// An interval of years to months.
+    IntervalYM(YearsMonths),
+    /// A REF CURSOR, or a nested cursor selected with a `CURSOR(...)` expression.
+    Cursor(Statement),
+    /// An object, as the name and value of each of its attributes.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Read a value from ODPI-C data, such as the buffer of a variable. LOBs are read in full, as a
+    /// string for character LOBs and as bytes otherwise, and ROWIDs are read as strings.
+    ///
+    /// * `data` - the data to read.
+    /// * `oracle_type_num` - the Oracle type of the data.
+    /// * `native_type_num` - the native type of the data.
+    #[doc(hidden)]
+    pub fn from_data(
+        data: &ODPIData,
+        oracle_type_num: ODPIOracleTypeNum,
+        native_type_num: ODPINativeTypeNum,
+    ) -> Result<Self> {
+        if data.is_null != 0 {
+            return Ok(Value::Null);
+        }
+
+        let value = unsafe {
+            match native_type_num {
+                ODPINativeTypeNum::Boolean => Value::Boolean(data.value.as_boolean != 0),
+                ODPINativeTypeNum::Int64 => Value::Int64(data.value.as_int_64),
+                ODPINativeTypeNum::Uint64 => Value::Uint64(data.value.as_uint_64),
+                ODPINativeTypeNum::Float => Value::Float(data.value.as_float),
+                ODPINativeTypeNum::Double => Value::Double(data.value.as_double),
+                ODPINativeTypeNum::Bytes => {
+                    let bytes = data.value.as_bytes;
+                    let slice = if bytes.ptr.is_null() {
+                        &[]
+                    } else {
+                        slice::from_raw_parts(bytes.ptr as *const u8, bytes.length as usize)
+                    };
+                    match oracle_type_num {
+                        ODPIOracleTypeNum::Raw | ODPIOracleTypeNum::LongRaw => {
+                            Value::Bytes(slice.to_vec())
+                        }
+                        _ => Value::String(String::from_utf8_lossy(slice).into_owned()),
+                    }
+                }
+                ODPINativeTypeNum::Timestamp => {
+                    // The fields of a TIMESTAMP WITH (LOCAL) TIME ZONE are in the time zone of
+                    // the value, so subtract its offset to get UTC. Other values have no offset.
+                    let ts = data.value.as_timestamp;
+                    let offset = Duration::seconds(
+                        i64::from(ts.tz_hour_offset) * 3600 + i64::from(ts.tz_minute_offset) * 60,
+                    );
+                    let timestamp = Utc
+                        .ymd_opt(i32::from(ts.year), u32::from(ts.month), u32::from(ts.day))
+                        .single()
+                        .and_then(|date| {
+                            date.and_hms_nano_opt(
+                                u32::from(ts.hour),
+                                u32::from(ts.minute),
+                                u32::from(ts.second),
+                                ts.fsecond,
+                            )
+                        })
+                        .and_then(|timestamp| timestamp.checked_sub_signed(offset));
+                    match timestamp {
+                        Some(timestamp) => Value::Timestamp(timestamp),
+                        None => return Err(ErrorKind::Conversion.into()),
+                    }
+                }
+                ODPINativeTypeNum::IntervalYM => {
+                    let interval = data.value.as_interval_ym;
+                    let mut years_months = YearsMonths::default();
+                    years_months.set_years(interval.years);
+                    years_months.set_months(interval.months);
+                    Value::IntervalYM(years_months)
+                }
+                ODPINativeTypeNum::IntervalDS => {
+                    let interval = data.value.as_interval_ds;
+                    Value::IntervalDS(
+                        Duration::days(i64::from(interval.days))
+                            + Duration::hours(i64::from(interval.hours))
+                            + Duration::minutes(i64::from(interval.minutes))
+                            + Duration::seconds(i64::from(interval.seconds))
+                            + Duration::nanoseconds(i64::from(interval.fseconds)),
+                    )
+                }
+                ODPINativeTypeNum::Lob => {
+                    Self::from_lob(&Self::fetched_lob(data), oracle_type_num)?
+                }
+                ODPINativeTypeNum::Rowid => {
+                    Value::String(Self::fetched_rowid(data).get_string_value()?)
+                }
+                ODPINativeTypeNum::Stmt => {
+                    // The statement is owned by the buffer it was read from, so take a reference
+                    // that is released when the `Statement` is dropped.
+                    let stmt = data.value.as_stmt;
+                    externs::dpiStmt_addRef(stmt);
+                    Value::Cursor(Statement::from(stmt))
+                }
+                other => {
+                    return Err(
+                        ErrorKind::FromSql(format!("{:?}", other), "Value".to_string()).into(),
+                    )
+                }
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// Read an object fetched into the buffer of a variable, such as an object column of a query.
+    /// The buffer keeps its own reference to the object, so a reference is added for the `Object`
+    /// that is read and released when it is dropped.
+    ///
+    /// * `data` - the data holding the object, which must not be null.
+    /// * `object_type` - the type of the object.
+    #[doc(hidden)]
+    pub fn from_fetched_object(data: &ODPIData, object_type: &ObjectType) -> Result<Self> {
+        let object = unsafe { data.value.as_object };
+        unsafe {
+            externs::dpiObject_addRef(object);
+        }
+        Self::from_object(&Object::from(object), object_type)
+    }
+
+    /// Get the LOB fetched into the buffer of a variable. The buffer keeps its own reference to the
+    /// LOB, so a reference is added for the `Lob` that is returned and released when it is dropped.
+    ///
+    /// * `data` - the data holding the LOB, which must not be null.
+    #[doc(hidden)]
+    pub fn fetched_lob(data: &ODPIData) -> Lob {
+        let lob = unsafe { data.value.as_lob };
+        unsafe {
+            externs::dpiLob_addRef(lob);
+        }
+        Lob::from(lob)
+    }
+
+    /// Get the ROWID fetched into the buffer of a variable, adding a reference in the same way as
+    /// `fetched_lob()`.
+    ///
+    /// * `data` - the data holding the ROWID, which must not be null.
+    #[doc(hidden)]
+    pub fn fetched_rowid(data: &ODPIData) -> Rowid {
+        let rowid = unsafe { data.value.as_rowid };
+        unsafe {
+            externs::dpiRowid_addRef(rowid);
+        }
+        Rowid::from(rowid)
+    }
+
+    /// Read the contents of a LOB, as a string for character LOBs and as bytes otherwise.
+    fn from_lob(lob: &Lob, oracle_type_num: ODPIOracleTypeNum) -> Result<Self> {
+        let bytes: Vec<u8> = lob
+            .read_bytes(1, lob.get_size()?)?
+            .into_iter()
+            .map(|byte| byte as u8)
+            .collect();
+
+        match oracle_type_num {
+            ODPIOracleTypeNum::Clob | ODPIOracleTypeNum::NClob => {
+                Ok(Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+            }
+            _ => Ok(Value::Bytes(bytes)),
+        }
+    }
+
+    /// Read the attributes of an object. Attributes that are themselves objects are read
+    /// recursively.
+    ///
+    /// * `object` - the object to read.
+    /// * `object_type` - the type of the object.
+    #[doc(hidden)]
+    pub fn from_object(object: &Object, object_type: &ObjectType) -> Result<Self> {
+        let info = object_type.get_info()?;
+        if info.is_collection != 0 {
+            return Err(ErrorKind::FromSql("collection".to_string(), "Value".to_string()).into());
+        }
+
+        let mut attributes = Vec::with_capacity(info.num_attributes as usize);
+        for attr in object_type.get_attributes(info.num_attributes)? {
+            let attr = ObjectAttr::from(attr);
+            let attr_info = attr.get_info()?;
+            let type_info = attr_info.type_info;
+            let name: String = ODPIStr::new(attr_info.name, attr_info.name_length).into();
+            let data = object.get_attribute_value(&attr, &attr_info)?;
+
+            // Unlike fetched values, object, LOB and ROWID attribute values are new references
+            // owned by the caller, which are released when their wrappers are dropped.
+            let value = match type_info.default_native_type_num {
+                _ if data.is_null != 0 => Value::Null,
+                ODPINativeTypeNum::Object => {
+                    let nested = Object::from(unsafe { data.value.as_object });
+                    Self::from_object(&nested, &ObjectType::from(type_info.object_type))?
+                }
+                ODPINativeTypeNum::Lob => Self::from_lob(
+                    &Lob::from(unsafe { data.value.as_lob }),
+                    type_info.oracle_type_num,
+                )?,
+                ODPINativeTypeNum::Rowid => {
+                    Value::String(Rowid::from(unsafe { data.value.as_rowid }).get_string_value()?)
+                }
+                native_type_num => {
+                    Self::from_data(&data, type_info.oracle_type_num, native_type_num)?
+                }
+            };
+
+            attr.release()?;
+            attributes.push((name, value));
+        }
+
+        Ok(Value::Object(attributes))
+    }
+
+    /// Returns true if the value is null.
+    pub fn is_null(&self) -> bool {
+        match *self {
+            Value::Null => true,
+            _ => false,
+        }
+    }
+
+    /// Describe the kind of value, for error messages.
+    #[doc(hidden)]
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            Value::Null => "null",
+            Value::Boolean(_) => "boolean",
+            Value::Int64(_) => "integer",
+            Value::Uint64(_) => "unsigned integer",
+            Value::Float(_) | Value::Double(_) => "floating point",
+            Value::String(_) => "string",
+            Value::Bytes(_) => "byte string",
+            Value::Timestamp(_) => "timestamp",
+            Value::IntervalDS(_) | Value::IntervalYM(_) => "interval",
+            Value::Cursor(_) => "cursor",
+            Value::Object(_) => "object",
+        }
+    }
+
+    /// Create the error returned when the value cannot be converted to the given type.
+    fn invalid(&self, target: &str) -> Error {
+        ErrorKind::FromSql(self.kind().to_string(), target.to_string()).into()
+    }
+}
+
+/// A type that can be created from a value read from a variable or a query column.
+pub trait FromSql: Sized {
+    /// Convert the value.
+    fn from_sql(value: Value) -> Result<Self>;
+}
+
+impl FromSql for Value {
+    fn from_sql(value: Value) -> Result<Self> {
+        Ok(value)
+    }
+}
+
+impl<T: FromSql> FromSql for Option<T> {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_sql(value).map(Some),
+        }
+    }
+}
+
+impl FromSql for i64 {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::Int64(v) => Ok(v),
+            Value::Uint64(v) => Ok(TryFrom::try_from(v)?),
+            Value::Boolean(v) => Ok(if v { 1 } else { 0 }),
+            // `as` would saturate doubles out of range, so only whole numbers in range convert.
+            Value::Double(v) if v.fract() == 0.0 && v >= -TWO_POW_63 && v < TWO_POW_63 => {
+                Ok(v as i64)
+            }
+            Value::String(ref v) if v.trim().parse::<i64>().is_ok() => Ok(v.trim().parse()?),
+            value => Err(value.invalid("i64")),
+        }
+    }
+}
+
+macro_rules! int_from_sql {
+    ($($t:ty),*) => {
+        $(
+            impl FromSql for $t {
+                fn from_sql(value: Value) -> Result<Self> {
+                    Ok(TryFrom::try_from(i64::from_sql(value)?)?)
+                }
+            }
+        )*
+    };
+}
+
+int_from_sql!(i8, i16, i32, u8, u16, u32);
+
+impl FromSql for u64 {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::Uint64(v) => Ok(v),
+            Value::String(ref v) if v.trim().parse::<u64>().is_ok() => Ok(v.trim().parse()?),
+            value => Ok(TryFrom::try_from(i64::from_sql(value)?)?),
+        }
+    }
+}
+
+impl FromSql for f64 {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::Double(v) => Ok(v),
+            Value::Float(v) => Ok(f64::from(v)),
+            Value::Int64(v) => Ok(v as f64),
+            Value::Uint64(v) => Ok(v as f64),
+            Value::String(ref v) => v.trim().parse().map_err(|_| value.invalid("f64")),
+            value => Err(value.invalid("f64")),
+        }
+    }
+}
+
+impl FromSql for f32 {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::Float(v) => Ok(v),
+            value => f64::from_sql(value).map(|v| v as f32),
+        }
+    }
+}
+
+impl FromSql for bool {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::Boolean(v) => Ok(v),
+            Value::Int64(v) => Ok(v != 0),
+            Value::Uint64(v) => Ok(v != 0),
+            Value::Double(v) => Ok(v != 0.0),
+            value => Err(value.invalid("bool")),
+        }
+    }
+}
+
+impl FromSql for String {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::String(v) => Ok(v),
+            Value::Int64(v) => Ok(v.to_string()),
+            Value::Uint64(v) => Ok(v.to_string()),
+            Value::Float(v) => Ok(v.to_string()),
+            Value::Double(v) => Ok(v.to_string()),
+            Value::Boolean(v) => Ok(v.to_string()),
+            Value::Timestamp(v) => Ok(v.to_rfc3339()),
+            value => Err(value.invalid("String")),
+        }
+    }
+}
+
+impl FromSql for Vec<u8> {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::Bytes(v) => Ok(v),
+            Value::String(v) => Ok(v.into_bytes()),
+            value => Err(value.invalid("Vec<u8>")),
+        }
+    }
+}
+
+impl FromSql for DateTime<Utc> {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::Timestamp(v) => Ok(v),
+            value => Err(value.invalid("DateTime<Utc>")),
+        }
+    }
+}
+
+impl FromSql for Duration {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::IntervalDS(v) => Ok(v),
+            value => Err(value.invalid("Duration")),
+        }
+    }
+}
+
+impl FromSql for YearsMonths {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::IntervalYM(v) => Ok(v),
+            value => Err(value.invalid("YearsMonths")),
+        }
+    }
+}
+
+impl FromSql for Statement {
+    fn from_sql(value: Value) -> Result<Self> {
+        match value {
+            Value::Cursor(v) => Ok(v),
+            value => Err(value.invalid("Statement")),
+        }
+    }
+}
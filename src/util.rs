@@ -7,7 +7,7 @@
 // modified, or distributed except according to those terms.
 
 //! `oic` utilities
-use error::{Error, Result};
+use error::{Error, ErrorKind, Result};
 use std::convert::TryFrom;
 use std::os::raw::c_char;
 use std::ptr;
@@ -149,6 +149,7 @@ macro_rules! try_from_upper_bounded {
 mod ptr_try_from_impls {
     use super::PrivateTryFromUsize;
 
+    try_from_upper_bounded!(u8);
     try_from_unbounded!(u16, u32, u64, u128);
     try_from_unbounded!(i32, i64, i128);
 }
@@ -158,7 +159,7 @@ mod ptr_try_from_impls {
 mod ptr_try_from_impls {
     use super::PrivateTryFromUsize;
 
-    try_from_upper_bounded!(u16);
+    try_from_upper_bounded!(u8, u16);
     try_from_unbounded!(u32, u64, u128);
     try_from_upper_bounded!(i32);
     try_from_unbounded!(i64, i128);
@@ -169,8 +170,65 @@ mod ptr_try_from_impls {
 mod ptr_try_from_impls {
     use super::PrivateTryFromUsize;
 
-    try_from_upper_bounded!(u16, u32);
+    try_from_upper_bounded!(u8, u16, u32);
     try_from_unbounded!(u64, u128);
     try_from_upper_bounded!(i32, i64);
     try_from_unbounded!(i128);
 }
+
+/// The alphabet used for base64 encoding.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode bytes as standard base64, with padding. Encoding data in pieces gives the same result
+/// as encoding it at once as long as every piece but the last is a multiple of 3 bytes long.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b0 = u32::from(chunk[0]);
+        let b1 = u32::from(*chunk.get(1).unwrap_or(&0));
+        let b2 = u32::from(*chunk.get(2).unwrap_or(&0));
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+
+        for (i, shift) in [18, 12, 6, 0].iter().enumerate() {
+            if i <= chunk.len() {
+                encoded.push(char::from(
+                    BASE64_ALPHABET[((triple >> shift) & 0x3f) as usize],
+                ));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Make sure a table or column name can be used in SQL as it is.
+pub fn check_identifier(name: &str) -> Result<()> {
+    let valid = |c: char| c.is_alphanumeric() || c == '_' || c == '$' || c == '#' || c == '.';
+    if name.is_empty() || !name.chars().all(valid) {
+        Err(ErrorKind::Identifier(name.to_string()).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Compute the CRC-32 (IEEE) checksum of bytes, the checksum used by zip and gzip.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
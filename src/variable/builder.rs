@@ -0,0 +1,76 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A `VarBuilder` collects the type and array settings used to create a variable, instead of
+//! passing them positionally to `Connection::new_var()`. It is usually created with
+//! `Var::builder()`, which infers the type of the variable from a `ToSql` type.
+use connection::Connection;
+use error::Result;
+use types::SqlType;
+use variable::Var;
+
+/// The options used to create a variable. These are usually built with a `VarBuilder`.
+#[derive(Builder, Clone, Copy, Debug, Getters)]
+#[builder(name = "VarBuilder")]
+pub struct VarOptions {
+    /// The type of the variable.
+    #[get = "pub"]
+    sql_type: SqlType,
+    /// The maximum number of rows that can be fetched or bound at one time, or the maximum number
+    /// of elements that can be stored in a PL/SQL array.
+    #[builder(default = "1")]
+    #[get = "pub"]
+    max_array_size: u32,
+    /// Does the variable refer to a PL/SQL array rather than to buffers used for binding or
+    /// fetching data?
+    #[builder(default)]
+    #[get = "pub"]
+    is_array: bool,
+}
+
+impl VarOptions {
+    /// Creates a variable using these options.
+    ///
+    /// * `conn` - the connection the variable is created on.
+    pub fn create(&self, conn: &Connection) -> Result<Var> {
+        let var = conn.new_var(
+            self.sql_type.oracle_type_num(),
+            self.sql_type.native_type_num(),
+            self.max_array_size,
+            self.sql_type.size(),
+            self.sql_type.size_is_bytes(),
+            self.is_array,
+        )?;
+        Ok(var.with_sql_type(self.sql_type))
+    }
+}
+
+impl VarBuilder {
+    /// Sets the size of the buffer used for each element of a variable transferred as byte
+    /// strings, keeping its type. This has no effect on other types.
+    ///
+    /// * `size` - the size, in characters for VARCHAR2 and NVARCHAR2 or bytes for RAW.
+    pub fn size(&mut self, size: u32) -> &mut Self {
+        let sql_type = match self.sql_type {
+            Some(SqlType::NVarchar(_)) => SqlType::NVarchar(size),
+            Some(SqlType::Raw(_)) => SqlType::Raw(size),
+            Some(SqlType::Varchar(_)) => SqlType::Varchar(size),
+            Some(sql_type) => sql_type,
+            None => return self,
+        };
+        self.sql_type(sql_type)
+    }
+
+    /// Builds the options and creates a variable.
+    ///
+    /// * `conn` - the connection the variable is created on.
+    pub fn create(&self, conn: &Connection) -> Result<Var> {
+        let options = self.build().map_err(|e| e.to_string())?;
+        options.create(conn)
+    }
+}
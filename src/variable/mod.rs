@@ -12,6 +12,7 @@
 //! are bound to statements by calling the function `Statement::bindByName()` or the function
 //! `Statement::bindByPos()`. They can also be used for fetching data from the database by calling
 //! the function `Statement::define()`.
+use connection::Connection;
 use error::{ErrorKind, Result};
 use lob::Lob;
 use object::Object;
@@ -22,16 +23,72 @@ use rowid::Rowid;
 use statement::Statement;
 use std::convert::TryFrom;
 use std::{ptr, slice};
-use util::ODPIStr;
+use types::{FromSql, SqlType, ToSql, Value};
+use util::{ODPIStr, PrivateTryFromUsize};
+
+pub mod builder;
+
+use self::builder::VarBuilder;
 
 /// This structure represents memory areas used for transferring data to and from the database and
 /// is available by handle to a calling application or driver.
 pub struct Var {
     /// The ODPI-C var
     inner: *mut ODPIVar,
+    /// The type the variable was created with, if it was created with a `VarBuilder`.
+    sql_type: Option<SqlType>,
 }
 
 impl Var {
+    /// Create a `VarBuilder` for a variable holding values of type `T`.
+    pub fn builder<T: ToSql>() -> VarBuilder {
+        let mut builder = VarBuilder::default();
+        builder.sql_type(T::default_sql_type());
+        builder
+    }
+
+    /// Create a PL/SQL array variable holding the given values, for binding to a PL/SQL
+    /// associative array (index-by table). The number of elements in the array is set to the
+    /// number of values.
+    ///
+    /// * `conn` - the connection the variable is created on.
+    /// * `values` - the values of the array.
+    pub fn from_slice<T: ToSql>(conn: &Connection, values: &[T]) -> Result<Self> {
+        let sql_type = match values.split_first() {
+            Some((first, rest)) => rest
+                .iter()
+                .map(|value| value.sql_type())
+                .fold(first.sql_type(), SqlType::widest),
+            None => T::default_sql_type(),
+        };
+        let num_elements = u32::private_try_from(values.len())?;
+
+        let var = Self::builder::<T>()
+            .sql_type(sql_type)
+            .max_array_size(num_elements.max(1))
+            .is_array(true)
+            .create(conn)?;
+
+        for (pos, value) in values.iter().enumerate() {
+            var.set(u32::private_try_from(pos)?, value)?;
+        }
+        var.set_num_elements_in_array(num_elements)?;
+
+        Ok(var)
+    }
+
+    /// Set the type of the variable.
+    #[doc(hidden)]
+    pub fn with_sql_type(mut self, sql_type: SqlType) -> Self {
+        self.sql_type = Some(sql_type);
+        self
+    }
+
+    /// Get the type the variable was created with, if it was created with a `VarBuilder`.
+    pub fn sql_type(&self) -> Option<SqlType> {
+        self.sql_type
+    }
+
     /// Get the `inner` value.
     #[doc(hidden)]
     pub fn inner(&self) -> *mut ODPIVar {
@@ -55,6 +112,23 @@ impl Var {
         )
     }
 
+    /// Get the value at the given array position, converted to `T`. The variable must have been
+    /// created with a `VarBuilder`.
+    ///
+    /// * `pos` - the array position in the variable which is to be read. The first position is 0.
+    pub fn get<T: FromSql>(&self, pos: u32) -> Result<T> {
+        let sql_type = self.sql_type.ok_or(ErrorKind::UntypedVar)?;
+
+        match self.get_data()?.get(pos as usize) {
+            Some(data) => T::from_sql(Value::from_data(
+                data,
+                sql_type.oracle_type_num(),
+                sql_type.native_type_num(),
+            )?),
+            None => Err(ErrorKind::Var("dpiVar_getData".to_string()).into()),
+        }
+    }
+
     /// Returns a pointer to an array of `ODPIData` structures used for transferring data to and
     /// from the database. These structures are allocated by the variable itself and are made
     /// available when the variable is first created using the function `Connection::new_var()`. If
@@ -77,6 +151,29 @@ impl Var {
         )
     }
 
+    /// Returns the data returned by a DML returning statement for the given iteration. For a
+    /// statement executed with `Statement::execute()` the position is 0, and for one executed with
+    /// `Statement::execute_many()` it is the iteration of interest. Each element of the returned
+    /// slice corresponds to one row affected by that iteration.
+    ///
+    /// * `pos` - the iteration of the DML returning statement. The first position is 0.
+    pub fn get_returned_data(&self, pos: u32) -> Result<&mut [ODPIData]> {
+        let mut num_elements = 0;
+        let mut data_arr_ptr = ptr::null_mut();
+
+        try_dpi!(
+            externs::dpiVar_getReturnedData(self.inner, pos, &mut num_elements, &mut data_arr_ptr),
+            {
+                if data_arr_ptr.is_null() || num_elements == 0 {
+                    Ok(&mut [])
+                } else {
+                    Ok(unsafe { slice::from_raw_parts_mut(data_arr_ptr, num_elements as usize) })
+                }
+            },
+            ErrorKind::Var("dpiVar_getReturnedData".to_string())
+        )
+    }
+
     /// Returns the number of elements in a PL/SQL index-by table if the variable was created as an
     /// array by the function `Connection::newVar()`. If the variable is one of the output bind
     /// variables of a DML returning statement, however, the value returned will correspond to the
@@ -102,6 +199,18 @@ impl Var {
         )
     }
 
+    /// Sets the value at the given array position. The value is converted to the type the variable
+    /// was created with, or written using its own type if the variable was not created with a
+    /// `VarBuilder`.
+    ///
+    /// * `pos` - the array position in the variable which is to be set. The first position is 0. If
+    /// the position exceeds the number of elements allocated by the variable an error is returned.
+    /// * `value` - the value which should be set.
+    pub fn set<T: ToSql + ?Sized>(&self, pos: u32, value: &T) -> Result<()> {
+        let sql_type = self.sql_type.unwrap_or_else(|| value.sql_type());
+        value.to_sql(self, pos, &sql_type)
+    }
+
     /// Sets the variable value to the specified string. In the case of the variable's Oracle type
     /// being DPI_ORACLE_TYPE_NUMBER, the string is converted to an Oracle number during the call to
     /// this function.
@@ -186,7 +295,10 @@ impl Var {
 
 impl From<*mut ODPIVar> for Var {
     fn from(inner: *mut ODPIVar) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            sql_type: None,
+        }
     }
 }
 
@@ -0,0 +1,140 @@
+use arrow_crate::array::{Array, Decimal128Array, StringArray, TimestampSecondArray};
+use arrow_crate::datatypes::{DataType, TimeUnit};
+use mimir::arrow::{arrow_type, parse_decimal};
+use mimir::enums::{ODPINativeTypeNum, ODPIOracleTypeNum};
+use mimir::flags;
+use mimir::Result;
+use mimir::{Connection, Context, ODPIDataTypeInfo, TypeInfo};
+use CREDS;
+
+fn type_info(oracle_type_num: ODPIOracleTypeNum, precision: i16, scale: i8, fs: u8) -> TypeInfo {
+    TypeInfo::new(ODPIDataTypeInfo {
+        oracle_type_num,
+        default_native_type_num: ODPINativeTypeNum::Invalid,
+        precision,
+        scale,
+        fs_precision: fs,
+        ..Default::default()
+    })
+}
+
+fn query_arrow_res(ctxt: &Context) -> Result<()> {
+    let mut ccp = ctxt.init_common_create_params()?;
+    ccp.set_encoding("UTF-8")?;
+    ccp.set_nchar_encoding("UTF-8")?;
+
+    let conn = Connection::create(
+        ctxt,
+        Some(&CREDS[0]),
+        Some(&CREDS[1]),
+        Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
+        Some(ccp),
+        None,
+    )?;
+
+    {
+        let batches = conn.query_arrow(
+            "select cast(level * 1.5 as number(10, 2)) amount, 'row ' || level label, \
+             date '2017-11-03' created from dual connect by level <= 3",
+            &[],
+        )?;
+        let schema = batches.schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Decimal128(10, 2));
+        assert_eq!(schema.field(1).data_type(), &DataType::Utf8);
+
+        let batches = batches.collect::<Result<Vec<_>>>()?;
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 3);
+
+        let amounts = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Decimal128Array>()
+            .expect("not a decimal array");
+        assert_eq!(amounts.value(2), 450);
+
+        let labels = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .expect("not a string array");
+        assert_eq!(labels.value(0), "row 1");
+
+        let created = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<TimestampSecondArray>()
+            .expect("not a timestamp array");
+        assert_eq!(created.value(0), 1_509_667_200);
+        assert!(!created.is_null(1));
+    }
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn query_arrow() {
+    check_with_ctxt!(query_arrow_res)
+}
+
+#[test]
+fn arrow_types() {
+    let cases = vec![
+        (
+            type_info(ODPIOracleTypeNum::Number, 10, 2, 0),
+            DataType::Decimal128(10, 2),
+        ),
+        (
+            type_info(ODPIOracleTypeNum::Number, 0, -127, 0),
+            DataType::Float64,
+        ),
+        (
+            type_info(ODPIOracleTypeNum::Number, 126, -127, 0),
+            DataType::Float64,
+        ),
+        (
+            type_info(ODPIOracleTypeNum::NativeFloat, 0, 0, 0),
+            DataType::Float32,
+        ),
+        (
+            type_info(ODPIOracleTypeNum::Date, 0, 0, 0),
+            DataType::Timestamp(TimeUnit::Second, None),
+        ),
+        (
+            type_info(ODPIOracleTypeNum::Timestamp, 0, 0, 6),
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+        ),
+        (
+            type_info(ODPIOracleTypeNum::Timestamp, 0, 0, 9),
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+        ),
+        (
+            type_info(ODPIOracleTypeNum::TimestampTz, 0, 0, 6),
+            DataType::Timestamp(TimeUnit::Microsecond, Some("+00:00".into())),
+        ),
+        (
+            type_info(ODPIOracleTypeNum::Varchar, 0, 0, 0),
+            DataType::Utf8,
+        ),
+        (type_info(ODPIOracleTypeNum::Raw, 0, 0, 0), DataType::Binary),
+    ];
+
+    for (type_info, expected) in cases {
+        assert_eq!(arrow_type(&type_info).expect("arrow_type failed"), expected);
+    }
+
+    assert!(arrow_type(&type_info(ODPIOracleTypeNum::Clob, 0, 0, 0)).is_err());
+}
+
+#[test]
+fn decimals() {
+    assert_eq!(parse_decimal("123.45", 2).expect("parse failed"), 12_345);
+    assert_eq!(parse_decimal("-1.5", 2).expect("parse failed"), -150);
+    assert_eq!(parse_decimal("42", 0).expect("parse failed"), 42);
+    assert_eq!(parse_decimal("1200", -2).expect("parse failed"), 12);
+    assert!(parse_decimal("1.234", 2).is_err());
+    assert!(parse_decimal("abc", 0).is_err());
+}
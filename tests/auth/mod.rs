@@ -0,0 +1,104 @@
+use mimir::{flags, Auth, ConnectionBuilder, Error};
+
+fn is_auth_error(err: &Error) -> bool {
+    format!("{}", err).starts_with("Invalid authentication")
+}
+
+#[test]
+fn auth_from_parts() {
+    let auth = Auth::from_parts(
+        Some("jozias"),
+        Some("secret"),
+        false,
+        flags::DPI_MODE_AUTH_DEFAULT,
+    )
+    .expect("unable to classify password auth");
+    assert_eq!(
+        auth,
+        Auth::Password {
+            user: "jozias".to_string(),
+            password: "secret".to_string(),
+        }
+    );
+
+    let auth = Auth::from_parts(
+        Some("jozias[app]"),
+        Some("secret"),
+        false,
+        flags::DPI_MODE_AUTH_DEFAULT,
+    )
+    .expect("unable to classify proxy auth");
+    assert_eq!(auth.username(), Some("jozias[app]".to_string()));
+    assert_eq!(auth.password(), Some("secret"));
+    assert!(!auth.external_auth());
+
+    let auth = Auth::from_parts(None, None, true, flags::DPI_MODE_AUTH_DEFAULT)
+        .expect("unable to classify external auth");
+    assert_eq!(auth, Auth::External);
+    assert!(auth.external_auth());
+
+    let auth = Auth::from_parts(None, None, true, flags::DPI_MODE_AUTH_SYSDBA)
+        .expect("unable to classify privileged auth");
+    assert_eq!(auth.auth_mode(), flags::DPI_MODE_AUTH_SYSDBA);
+    assert!(auth.external_auth());
+    assert_eq!(auth.username(), None);
+}
+
+#[test]
+fn auth_rejects_invalid() {
+    let default = flags::DPI_MODE_AUTH_DEFAULT;
+    let invalid = [
+        Auth::from_parts(None, None, false, default),
+        Auth::from_parts(Some("jozias"), None, false, default),
+        Auth::from_parts(None, Some("secret"), false, default),
+        Auth::from_parts(Some("jozias"), Some("secret"), true, default),
+        Auth::from_parts(Some("jozias[app"), Some("secret"), false, default),
+        Auth::from_parts(Some("jozias[]"), Some("secret"), false, default),
+    ];
+
+    for res in invalid.iter() {
+        match *res {
+            Ok(ref auth) => assert!(false, "expected an error, got {:?}", auth),
+            Err(ref e) => assert!(is_auth_error(e)),
+        }
+    }
+
+    let auth = Auth::Privileged {
+        user: Some("sys".to_string()),
+        password: None,
+        mode: flags::DPI_MODE_AUTH_SYSDBA,
+    };
+    assert!(auth.validate().is_err());
+
+    let auth = Auth::Privileged {
+        user: None,
+        password: None,
+        mode: default,
+    };
+    assert!(auth.validate().is_err());
+}
+
+#[test]
+fn auth_builder() {
+    let auth = Auth::Proxy {
+        user: "jozias".to_string(),
+        password: "secret".to_string(),
+        proxy_user: "app".to_string(),
+    };
+    let options = ConnectionBuilder::default()
+        .auth(&auth)
+        .expect("unable to set auth")
+        .build()
+        .expect("unable to build options");
+    assert_eq!(options.user(), "jozias[app]");
+    assert_eq!(options.password(), "secret");
+    assert!(!*options.external_auth());
+
+    let options = ConnectionBuilder::default()
+        .auth(&Auth::External)
+        .expect("unable to set auth")
+        .build()
+        .expect("unable to build options");
+    assert_eq!(options.user(), "");
+    assert!(*options.external_auth());
+}
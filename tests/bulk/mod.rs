@@ -0,0 +1,173 @@
+use mimir::enums::ODPINativeTypeNum;
+use mimir::enums::ODPIOracleTypeNum;
+use mimir::flags;
+use mimir::Result;
+use mimir::{
+    BulkInsert, BulkLoader, Connection, Context, CsvBuilder, CsvOptions, RowOutcome, SqlType, ToSql,
+};
+use rand::{self, Rng};
+use CREDS;
+
+fn bulk_insert_res(ctxt: &Context) -> Result<()> {
+    let mut ccp = ctxt.init_common_create_params()?;
+    ccp.set_encoding("UTF-8")?;
+    ccp.set_nchar_encoding("UTF-8")?;
+
+    let conn = Connection::create(
+        ctxt,
+        Some(&CREDS[0]),
+        Some(&CREDS[1]),
+        Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
+        Some(ccp),
+        None,
+    )?;
+
+    {
+        let mut bulk = BulkInsert::with_array_size(
+            &conn,
+            "insert into username values (:id, :username)",
+            &[SqlType::Int64, SqlType::Varchar(300)],
+            2,
+        )?;
+        assert_eq!(bulk.array_size(), 2);
+
+        let mut rng = rand::thread_rng();
+        let too_long = "x".repeat(300);
+        let usernames = ["jozias", too_long.as_str(), "jozias"];
+
+        for (idx, username) in usernames.iter().enumerate() {
+            let id = rng.gen::<i32>().abs();
+            assert_eq!(bulk.add_row(&[&id, username])?, idx);
+        }
+        assert_eq!(bulk.pending(), 1);
+        assert!(bulk.add_row(&[&1]).is_err());
+
+        let outcomes = bulk.flush()?;
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(outcomes[0], RowOutcome::Success(1));
+        match outcomes[1] {
+            RowOutcome::Failed(ref info) => assert_eq!(info.code(), 12899),
+            _ => assert!(false),
+        }
+        assert_eq!(outcomes[2], RowOutcome::Success(1));
+        assert!(bulk.flush()?.is_empty());
+    }
+
+    {
+        let mut bulk = BulkInsert::with_array_size(
+            &conn,
+            "insert into mimir_no_such_table values (:id)",
+            &[SqlType::Int64],
+            1,
+        )?;
+        assert!(bulk.add_row(&[&1]).is_err());
+        assert_eq!(bulk.pending(), 1);
+        assert!(bulk.flush().is_err());
+        assert_eq!(bulk.pending(), 1);
+
+        assert!(BulkInsert::with_array_size(
+            &conn,
+            "insert into username values (:id, :username)",
+            &[SqlType::Int64, SqlType::Varchar(300)],
+            65_537,
+        )
+        .is_err());
+    }
+
+    {
+        let mut rng = rand::thread_rng();
+        let mut ids = [rng.gen::<i32>().abs(), rng.gen::<i32>().abs()];
+        ids.sort();
+        let csv = format!(
+            "ID,USERNAME\n\
+             {},jozias\n\
+             two,jozias\n\
+             \n\
+             {},\"multi\n\
+             line\"\n\
+             4,jozias,extra\n",
+            ids[0], ids[1]
+        );
+        let summary =
+            BulkLoader::new(&conn).from_csv(csv.as_bytes(), "username", &["id", "username"])?;
+        assert_eq!(*summary.loaded(), 2);
+        let rejected = summary.rejected();
+        assert_eq!(rejected.len(), 2);
+        assert_eq!(*rejected[0].line(), 3);
+        assert_eq!(rejected[0].reason(), "field 1: 'two' is not an integer");
+        assert_eq!(*rejected[1].line(), 7);
+        assert_eq!(rejected[1].reason(), "expected 2 fields, found 3");
+        assert!(BulkLoader::new(&conn)
+            .from_csv(csv.as_bytes(), "username; drop table x", &["id"])
+            .is_err());
+
+        let mut rows = conn.query(
+            "select id, username from username where id in (:1, :2) order by id",
+            &[&ids[0], &ids[1]],
+        )?;
+        let options = CsvBuilder::default()
+            .delimiter(';')
+            .build()
+            .expect("invalid options");
+        let mut csv = Vec::new();
+        assert_eq!(rows.write_csv(&mut csv, &options)?, 2);
+        assert_eq!(
+            String::from_utf8_lossy(&csv),
+            format!(
+                "ID;USERNAME\n{};jozias\n{};\"multi\nline\"\n",
+                ids[0], ids[1]
+            )
+        );
+    }
+
+    conn.rollback()?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn bulk_insert() {
+    check_with_ctxt!(bulk_insert_res)
+}
+
+#[test]
+fn sql_types() {
+    assert_eq!(SqlType::Int64.oracle_type_num(), ODPIOracleTypeNum::Number);
+    assert_eq!(SqlType::Int64.native_type_num(), ODPINativeTypeNum::Int64);
+    assert_eq!(
+        SqlType::Date.native_type_num(),
+        ODPINativeTypeNum::Timestamp
+    );
+    assert_eq!(SqlType::Varchar(30).size(), 30);
+    assert!(!SqlType::Varchar(30).size_is_bytes());
+    assert!(SqlType::Raw(16).size_is_bytes());
+    assert_eq!(SqlType::Double.size(), 0);
+
+    assert_eq!(1_i32.sql_type(), SqlType::Int64);
+    assert_eq!(1_u64.sql_type(), SqlType::Uint64);
+    assert_eq!("jozias".sql_type(), SqlType::Varchar(6));
+    assert_eq!(vec![0_u8; 4].sql_type(), SqlType::Raw(4));
+    assert_eq!(None::<i64>.sql_type(), SqlType::Varchar(1));
+    assert_eq!(Some(1.5).sql_type(), SqlType::Double);
+}
+
+#[test]
+fn csv_options() {
+    let options = CsvOptions::default();
+    assert_eq!(*options.delimiter(), ',');
+    assert_eq!(*options.quote(), '"');
+    assert!(*options.has_headers());
+    assert_eq!(options.null_marker(), "");
+    assert_eq!(options.date_format(), "%Y-%m-%d %H:%M:%S%.f");
+    assert_eq!(*options.array_size(), 100);
+
+    let options = CsvBuilder::default()
+        .has_headers(false)
+        .null_marker("NULL")
+        .build()
+        .expect("invalid options");
+    assert!(!*options.has_headers());
+    assert_eq!(options.null_marker(), "NULL");
+    assert_eq!(*options.delimiter(), ',');
+}
@@ -0,0 +1,115 @@
+#[allow(dead_code)]
+#[path = "../../src/bin/mimir/input.rs"]
+mod input;
+
+use self::input::{parse_command, Buffer, Command, Input};
+
+fn statement(sql: &str) -> Option<Input> {
+    Some(Input::Statement(sql.to_string()))
+}
+
+#[test]
+fn sql_statements() {
+    let mut buffer = Buffer::default();
+    assert_eq!(buffer.push("   "), Ok(None));
+    assert_eq!(buffer.push("select *"), Ok(None));
+    assert!(!buffer.is_empty());
+    assert_eq!(
+        buffer.push("from dual;"),
+        Ok(statement("select *\nfrom dual"))
+    );
+    assert!(buffer.is_empty());
+
+    assert_eq!(buffer.push("/"), Ok(statement("select *\nfrom dual")));
+    assert_eq!(buffer.push("exit"), Ok(Some(Input::Command(Command::Quit))));
+
+    assert_eq!(buffer.push("create"), Ok(None));
+    assert_eq!(
+        buffer.push("table t (id number);"),
+        Ok(statement("create\ntable t (id number)"))
+    );
+    assert!(Buffer::default().push("/").is_err());
+}
+
+#[test]
+fn plsql_blocks() {
+    let mut buffer = Buffer::default();
+    assert_eq!(buffer.push("begin"), Ok(None));
+    assert_eq!(buffer.push("  null;"), Ok(None));
+    assert_eq!(buffer.push("end;"), Ok(None));
+    assert_eq!(buffer.push("/"), Ok(statement("begin\n  null;\nend;")));
+    assert_eq!(buffer.push("/"), Ok(statement("begin\n  null;\nend;")));
+
+    assert_eq!(buffer.push("CREATE OR REPLACE"), Ok(None));
+    assert_eq!(buffer.push("PROCEDURE p AS"), Ok(None));
+    assert_eq!(buffer.push("BEGIN NULL; END;"), Ok(None));
+    assert_eq!(
+        buffer.push("/"),
+        Ok(statement(
+            "CREATE OR REPLACE\nPROCEDURE p AS\nBEGIN NULL; END;"
+        ))
+    );
+
+    assert_eq!(buffer.push("-- recompute"), Ok(None));
+    assert_eq!(buffer.push("begin null; end;"), Ok(None));
+    assert_eq!(
+        buffer.push("/"),
+        Ok(statement("-- recompute\nbegin null; end;"))
+    );
+
+    assert_eq!(buffer.push("declare x number;"), Ok(None));
+    buffer.clear();
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn commands() {
+    assert_eq!(parse_command("q"), Ok(Command::Quit));
+    assert_eq!(parse_command("?"), Ok(Command::Help));
+    assert_eq!(parse_command("timing"), Ok(Command::Timing));
+    assert_eq!(parse_command("x"), Ok(Command::Vertical));
+    assert_eq!(
+        parse_command("d username"),
+        Ok(Command::Describe("username".to_string()))
+    );
+    assert!(parse_command("d").is_err());
+    assert_eq!(parse_command("o"), Ok(Command::Output(None)));
+    assert_eq!(
+        parse_command("o  out.csv "),
+        Ok(Command::Output(Some("out.csv".to_string())))
+    );
+    assert_eq!(parse_command("set"), Ok(Command::Set(None)));
+    assert_eq!(
+        parse_command("set :id 42"),
+        Ok(Command::Set(Some(("ID".to_string(), "42".to_string()))))
+    );
+    assert_eq!(
+        parse_command("set name two words"),
+        Ok(Command::Set(Some((
+            "NAME".to_string(),
+            "two words".to_string()
+        ))))
+    );
+    assert_eq!(
+        parse_command("unset :id"),
+        Ok(Command::Unset("ID".to_string()))
+    );
+    assert!(parse_command("unset").is_err());
+    assert_eq!(
+        parse_command("nope"),
+        Err("Unknown command '\\nope', try \\?".to_string())
+    );
+
+    let mut buffer = Buffer::default();
+    assert_eq!(
+        buffer.push("\\set id 1"),
+        Ok(Some(Input::Command(Command::Set(Some((
+            "ID".to_string(),
+            "1".to_string()
+        ))))))
+    );
+    assert_eq!(
+        buffer.push("\\o"),
+        Ok(Some(Input::Command(Command::Output(None))))
+    );
+}
@@ -0,0 +1,108 @@
+use mimir::config::Config;
+use std::env;
+use std::fs::File;
+use std::io::Write;
+
+const PROFILES: &str = r#"
+[orders]
+user = "orders"
+password = "secret"
+connect_string = "//db.example.com:1521/ORCL"
+encoding = "UTF-8"
+min_sessions = 1
+max_sessions = 10
+session_increment = 1
+stmt_cache_size = 50
+
+[reports]
+user = "reports"
+connect_string = "//db.example.com:1521/ORCL"
+"#;
+
+#[test]
+fn config_profiles() {
+    let config = Config::from_toml(PROFILES).expect("unable to parse config");
+    assert_eq!(config.names(), vec!["orders", "reports"]);
+
+    let orders = config.profile("orders").expect("missing profile");
+    assert_eq!(orders.user().as_ref().map(|s| s.as_str()), Some("orders"));
+    assert_eq!(
+        orders.password().expect("bad password"),
+        Some("secret".to_string())
+    );
+    assert_eq!(
+        orders.connect_string().as_ref().map(|s| s.as_str()),
+        Some("//db.example.com:1521/ORCL")
+    );
+    assert_eq!(
+        orders.encoding().as_ref().map(|s| s.as_str()),
+        Some("UTF-8")
+    );
+    assert_eq!(*orders.min_sessions(), Some(1));
+    assert_eq!(*orders.max_sessions(), Some(10));
+    assert_eq!(*orders.session_increment(), Some(1));
+    assert_eq!(*orders.stmt_cache_size(), Some(50));
+
+    let debug = format!("{:?}", orders);
+    assert!(!debug.contains("secret"));
+    assert!(debug.contains("password: Some(\"<redacted>\")"));
+    assert!(debug.contains("user: Some(\"orders\")"));
+
+    let reports = config.profile("reports").expect("missing profile");
+    assert_eq!(reports.password().expect("bad password"), None);
+    assert_eq!(*reports.max_sessions(), None);
+
+    assert!(config.profile("missing").is_err());
+    assert!(Config::from_toml("[orders]\nusername = \"orders\"\n").is_err());
+}
+
+#[test]
+fn config_env_overrides() {
+    let mut path = env::temp_dir();
+    path.push("mimir-config-test-password");
+    {
+        let mut file = File::create(&path).expect("unable to create password file");
+        file.write_all(b"from-file\n")
+            .expect("unable to write password file");
+    }
+
+    env::set_var("MIMIR_ENV_TEST_PASSWORD_FILE", &path);
+    env::set_var("MIMIR_ENV_TEST_MAX_SESSIONS", "20");
+    env::set_var("MIMIR_ONLY_ENV_USER", "scott");
+
+    let config = Config::from_toml("[env-test]\nuser = \"orders\"\nmax_sessions = 10\n")
+        .expect("unable to parse config");
+
+    let profile = config.profile("env-test").expect("missing profile");
+    assert_eq!(profile.user().as_ref().map(|s| s.as_str()), Some("orders"));
+    assert_eq!(
+        profile.password().expect("bad password"),
+        Some("from-file".to_string())
+    );
+    assert_eq!(*profile.max_sessions(), Some(20));
+
+    let env_only = config.profile("only-env").expect("missing profile");
+    assert_eq!(env_only.user().as_ref().map(|s| s.as_str()), Some("scott"));
+
+    env::set_var("MIMIR_FILE_TEST_PASSWORD_FILE", &path);
+    let config =
+        Config::from_toml("[file-test]\npassword = \"secret\"\n").expect("unable to parse config");
+    let profile = config.profile("file-test").expect("missing profile");
+    assert_eq!(
+        profile.password().expect("bad password"),
+        Some("from-file".to_string())
+    );
+
+    env::set_var("MIMIR_FILE_TEST_PASSWORD", "from-env");
+    let profile = config.profile("file-test").expect("missing profile");
+    assert_eq!(
+        profile.password().expect("bad password"),
+        Some("from-env".to_string())
+    );
+
+    env::set_var("MIMIR_ENV_TEST_MAX_SESSIONS", "lots");
+    let err = config
+        .profile("env-test")
+        .expect_err("invalid override accepted");
+    assert!(err.to_string().contains("MIMIR_ENV_TEST_MAX_SESSIONS"));
+}
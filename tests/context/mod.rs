@@ -1,6 +1,7 @@
+use chrono::{TimeZone, Utc};
 use mimir::Result;
 use mimir::{enums, flags};
-use mimir::{AppContext, Context, ODPISubscrMessage};
+use mimir::{AppContext, Context, ODPISubscrMessage, ShardingKey};
 
 extern "C" fn subscr_callback(
     _ctxt: *mut ::std::os::raw::c_void,
@@ -96,6 +97,23 @@ fn conn_cp(ctxt: &Context) -> Result<()> {
     assert_eq!(conn.get_out_tag(), "");
     assert!(!conn.get_out_tag_found());
 
+    assert!(conn.get_sharding_key().is_none());
+    let mut sharding_key = ShardingKey::new();
+    sharding_key
+        .varchar("EMEA")?
+        .number(42)?
+        .date(Utc.ymd(2017, 11, 1).and_hms(0, 0, 0))?
+        .raw(&[0xde, 0xad, 0xbe, 0xef])?;
+    conn.set_sharding_key(sharding_key)?;
+    let mut super_sharding_key = ShardingKey::new();
+    super_sharding_key.varchar("EUROPE")?;
+    conn.set_super_sharding_key(super_sharding_key)?;
+
+    assert_eq!(conn.get_sharding_key().map(|key| key.len()), Some(4));
+    assert_eq!(conn.get_super_sharding_key().map(|key| key.len()), Some(1));
+    assert_eq!(conn.inner().num_sharding_key_columns, 4);
+    assert_eq!(conn.inner().num_super_sharding_key_columns, 1);
+
     Ok(())
 }
 
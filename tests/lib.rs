@@ -1,27 +1,46 @@
-#![feature(try_from)]
 #[macro_use]
 extern crate lazy_static;
 #[macro_use]
 mod macros;
 #[macro_use]
+extern crate serde_derive;
+#[macro_use]
 extern crate slog;
 
+#[cfg(feature = "arrow")]
+extern crate arrow as arrow_crate;
 extern crate chrono;
 extern crate mimir;
+#[cfg(feature = "r2d2")]
+extern crate r2d2 as r2d2_crate;
 extern crate rand;
 extern crate slog_async;
 extern crate slog_term;
 
+#[cfg(feature = "arrow")]
+mod arrow;
+mod auth;
+mod builder;
+mod bulk;
+#[cfg(feature = "cli")]
+mod cli;
+mod config;
 mod connection;
 mod context;
 mod dequeue;
 mod enqueue;
 mod lob;
 mod message;
+mod migrate;
 #[cfg(any(target_arch = "linux", target_arch = "windows"))]
 mod objecttype;
+mod plsql;
 mod pool;
+#[cfg(feature = "r2d2")]
+mod r2d2;
+mod serde;
 mod statement;
+mod transaction;
 mod variable;
 
 use std::env;
@@ -0,0 +1,191 @@
+use mimir::flags;
+use mimir::migrate::{self, is_plsql, split, MigrateBuilder, Migration, Migrator};
+use mimir::Result;
+use mimir::{Connection, Context};
+use rand::{self, Rng};
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use CREDS;
+
+fn temp_dir() -> PathBuf {
+    let mut rng = rand::thread_rng();
+    let dir = env::temp_dir().join(format!("mimir-migrate-{}", rng.gen::<u32>()));
+    fs::create_dir_all(&dir).expect("unable to create the directory");
+    dir
+}
+
+fn write_script(dir: &PathBuf, name: &str, sql: &str) {
+    let mut file = File::create(dir.join(name)).expect("unable to create the script");
+    file.write_all(sql.as_bytes())
+        .expect("unable to write the script");
+}
+
+fn migrate_res(ctxt: &Context) -> Result<()> {
+    let mut ccp = ctxt.init_common_create_params()?;
+    ccp.set_encoding("UTF-8")?;
+    ccp.set_nchar_encoding("UTF-8")?;
+
+    let conn = Connection::create(
+        ctxt,
+        Some(&CREDS[0]),
+        Some(&CREDS[1]),
+        Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
+        Some(ccp),
+        None,
+    )?;
+
+    let mut rng = rand::thread_rng();
+    let suffix = rng.gen::<u32>();
+    let table = format!("MIGRATE_TEST_{}", suffix);
+    let history = format!("MIGRATE_HISTORY_{}", suffix);
+
+    let dir = temp_dir();
+    write_script(
+        &dir,
+        "V1__create_table.sql",
+        &format!(
+            "create table {} (id number(10), name varchar2(20));\n",
+            table
+        ),
+    );
+    write_script(
+        &dir,
+        "V2__insert_rows.sql",
+        &format!(
+            "insert into {0} values (1, 'a;b');\nbegin\n  insert into {0} values (2, 'c');\nend;\n/\n",
+            table
+        ),
+    );
+
+    let options = MigrateBuilder::default()
+        .table(history.clone())
+        .build()
+        .expect("invalid options");
+    let migrator = Migrator::with_options(&conn, options);
+    assert_eq!(migrator.migrate(&dir)?, vec![1, 2]);
+    assert_eq!(migrator.migrate(&dir)?, Vec::<u64>::new());
+    assert_eq!(migrator.applied()?.len(), 2);
+
+    {
+        let mut rows = conn.query(&format!("select count(*) from {}", table), &[])?;
+        let row = rows.next().expect("no row")?;
+        assert_eq!(row.get::<i64>(0)?, 2);
+    }
+
+    write_script(
+        &dir,
+        "V2__insert_rows.sql",
+        &format!("insert into {} values (3, 'd');\n", table),
+    );
+    assert!(migrator.migrate(&dir).is_err());
+
+    for name in &[&table, &history] {
+        let stmt = conn.prepare_stmt(Some(&format!("drop table {}", name)), None, false)?;
+        stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    }
+    fs::remove_dir_all(&dir)?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn migrate() {
+    check_with_ctxt!(migrate_res)
+}
+
+#[test]
+fn split_sql() {
+    let script = "-- create the table\n\
+                  create table t (id number);\n\
+                  insert into t values (1); insert into t values (2);\n\
+                  select 1\n  from dual\n/\n";
+    assert_eq!(
+        split(script),
+        vec![
+            "-- create the table\ncreate table t (id number)",
+            "insert into t values (1)",
+            "insert into t values (2)",
+            "select 1\n  from dual",
+        ]
+    );
+}
+
+#[test]
+fn plsql_detection() {
+    assert!(is_plsql("BEGIN"));
+    assert!(is_plsql("declare\n  x number;"));
+    assert!(is_plsql("create or replace editionable package body p as"));
+    assert!(is_plsql("CREATE OR REPLACE\nTRIGGER t"));
+    assert!(is_plsql("create type t as object (id number)"));
+    assert!(is_plsql("-- fix the totals\n/* nightly */ begin"));
+    assert!(!is_plsql("create table t (id number)"));
+    assert!(!is_plsql("create or replace view v as select 1 from dual"));
+    assert!(!is_plsql("select 'begin' from dual"));
+    assert!(!is_plsql("-- begin\nselect 1 from dual"));
+    assert!(!is_plsql(""));
+}
+
+#[test]
+fn split_plsql() {
+    let script = "create or replace procedure p as\nbegin\n  null;\nend;\n/\n\
+                  declare\n  x number;\nbegin\n  x := 1;\nend;\n/\n\
+                  begin\n  null;\nend;\n";
+    assert_eq!(
+        split(script),
+        vec![
+            "create or replace procedure p as\nbegin\n  null;\nend;",
+            "declare\n  x number;\nbegin\n  x := 1;\nend;",
+            "begin\n  null;\nend;",
+        ]
+    );
+}
+
+#[test]
+fn split_quotes_and_comments() {
+    let script = "insert into t values ('a;b', 'it''s', q'[x;y]');\n\
+                  /* a comment;\n/\n*/\n\
+                  select \"A;B\" from t;\n\
+                  -- only a comment;\n";
+    assert_eq!(
+        split(script),
+        vec![
+            "insert into t values ('a;b', 'it''s', q'[x;y]')",
+            "/* a comment;\n/\n*/\nselect \"A;B\" from t",
+        ]
+    );
+}
+
+#[test]
+fn load_migrations() {
+    let dir = temp_dir();
+    write_script(&dir, "V10__add_index.sql", "create index i on t (id);\n");
+    write_script(
+        &dir,
+        "V2__create_table.sql",
+        "create table t (id number);\r\n",
+    );
+    write_script(&dir, "README.md", "not a migration");
+
+    let migrations = migrate::load(&dir).expect("load failed");
+    assert_eq!(migrations.len(), 2);
+    assert_eq!(*migrations[0].version(), 2);
+    assert_eq!(migrations[0].description(), "create table");
+    assert_eq!(migrations[0].script(), "V2__create_table.sql");
+    assert_eq!(*migrations[1].version(), 10);
+    assert_eq!(
+        migrations[0].checksum(),
+        Migration::new(2, "", "", "create table t (id number);\n").checksum()
+    );
+
+    write_script(&dir, "V3__ajouter_café.sql", "select 1 from dual;\n");
+    let migration = Migration::from_file(dir.join("V3__ajouter_café.sql")).expect("invalid name");
+    assert_eq!(migration.description(), "ajouter café");
+    assert!(Migration::from_file(dir.join("V4__éabc")).is_err());
+
+    write_script(&dir, "create_table.sql", "create table t (id number);\n");
+    assert!(migrate::load(&dir).is_err());
+    fs::remove_dir_all(&dir).expect("unable to remove the directory");
+}
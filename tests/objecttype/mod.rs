@@ -294,3 +294,76 @@ fn obj_type(ctxt: &Context) -> Result<()> {
 fn objecttype() {
     check_with_ctxt!(obj_type)
 }
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct SubObject {
+    subnumbervalue: i64,
+    substringvalue: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct UdtObject {
+    numbervalue: i64,
+    stringvalue: String,
+    subobjectvalue: SubObject,
+    subobjectarray: Option<Vec<SubObject>>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct ObjectRow {
+    id: i64,
+    obj: UdtObject,
+}
+
+fn deserialize_res(ctxt: &Context) -> Result<()> {
+    let mut ccp = ctxt.init_common_create_params()?;
+    ccp.set_encoding("UTF-8")?;
+    ccp.set_nchar_encoding("UTF-8")?;
+
+    let conn = Connection::create(
+        ctxt,
+        Some(&CREDS[2]),
+        Some(&CREDS[3]),
+        Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
+        Some(ccp),
+        None,
+    )?;
+
+    let rows = conn.query(
+        "select level as id, \
+         udt_Object(level, 'jozias', 'mimir', null, null, udt_SubObject(level * 2, 'nested'), null) \
+         as obj from dual connect by level <= 2",
+        &[],
+    )?;
+    let objects = rows
+        .deserialize::<ObjectRow>()
+        .collect::<Result<Vec<ObjectRow>>>()?;
+    assert_eq!(objects.len(), 2);
+    for (idx, object) in objects.iter().enumerate() {
+        let level = idx as i64 + 1;
+        assert_eq!(
+            *object,
+            ObjectRow {
+                id: level,
+                obj: UdtObject {
+                    numbervalue: level,
+                    stringvalue: "jozias".to_string(),
+                    subobjectvalue: SubObject {
+                        subnumbervalue: level * 2,
+                        substringvalue: "nested".to_string(),
+                    },
+                    subobjectarray: None,
+                },
+            }
+        );
+    }
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn deserialize() {
+    check_with_ctxt!(deserialize_res)
+}
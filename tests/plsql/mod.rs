@@ -0,0 +1,118 @@
+use mimir::flags;
+use mimir::Result;
+use mimir::{Arg, Connection, Context, InOutParam, Out, Outputs, Rows, SqlType, Statement};
+use CREDS;
+
+fn call_res(ctxt: &Context) -> Result<()> {
+    let mut ccp = ctxt.init_common_create_params()?;
+    ccp.set_encoding("UTF-8")?;
+    ccp.set_nchar_encoding("UTF-8")?;
+
+    let conn = Connection::create(
+        ctxt,
+        Some(&CREDS[0]),
+        Some(&CREDS[1]),
+        Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
+        Some(ccp),
+        None,
+    )?;
+
+    let len: i64 = conn.call_func("length", &mut [Arg::In(&"jozias")])?;
+    assert_eq!(len, 6);
+
+    conn.call_proc("dbms_output.enable", &mut [])?;
+    conn.call_proc("dbms_output.put_line", &mut [Arg::In(&"mimir")])?;
+    let outputs = conn.call_proc(
+        "dbms_output.get_line",
+        &mut [Out::<String>::new(), Out::<i64>::new()],
+    )?;
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs.get::<String>(0)?, "mimir");
+    assert_eq!(outputs.get::<i64>(1)?, 0);
+
+    conn.call_proc("dbms_output.disable", &mut [])?;
+    assert!(conn.call_proc("dbms_output.enable; drop", &mut []).is_err());
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn call() {
+    check_with_ctxt!(call_res)
+}
+
+fn in_out_cursor_res(ctxt: &Context) -> Result<()> {
+    let mut ccp = ctxt.init_common_create_params()?;
+    ccp.set_encoding("UTF-8")?;
+    ccp.set_nchar_encoding("UTF-8")?;
+
+    let conn = Connection::create(
+        ctxt,
+        Some(&CREDS[0]),
+        Some(&CREDS[1]),
+        Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
+        Some(ccp),
+        None,
+    )?;
+
+    let create = conn.prepare_stmt(
+        Some(
+            "create or replace procedure mimir_double_levels(n in out number, c out sys_refcursor) \
+             as begin n := n * 2; open c for select level from dual connect by level <= n; end;",
+        ),
+        None,
+        false,
+    )?;
+    create.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+
+    let mut n = 3_i64;
+    let outputs = conn.call_proc(
+        "mimir_double_levels",
+        &mut [Arg::InOut(&mut n), Out::<Statement>::new()],
+    )?;
+    assert_eq!(n, 6);
+    assert_eq!(outputs.get::<i64>(0)?, 6);
+
+    let rows = Rows::new(outputs.get::<Statement>(1)?)?;
+    let mut levels = Vec::new();
+    for row in rows {
+        levels.push(row?.get::<i64>(0)?);
+    }
+    assert_eq!(levels, vec![1, 2, 3, 4, 5, 6]);
+
+    let drop = conn.prepare_stmt(Some("drop procedure mimir_double_levels"), None, false)?;
+    drop.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn in_out_cursor() {
+    check_with_ctxt!(in_out_cursor_res)
+}
+
+#[test]
+fn args() {
+    match Out::<i64>::new() {
+        Arg::Output(sql_type) => assert_eq!(sql_type, SqlType::Int64),
+        _ => assert!(false),
+    }
+    match Out::<Option<String>>::new() {
+        Arg::Output(sql_type) => assert_eq!(sql_type, SqlType::Varchar(4000)),
+        _ => assert!(false),
+    }
+
+    assert_eq!(1_i32.out_sql_type(), SqlType::Int64);
+    assert_eq!("jozias".to_string().out_sql_type(), SqlType::Varchar(4000));
+
+    let outputs = Outputs::default();
+    assert!(outputs.is_empty());
+    match outputs.get::<i64>(0) {
+        Ok(_) => assert!(false),
+        Err(e) => assert_eq!(format!("{}", e), "No OUT or IN OUT value for argument 0!"),
+    }
+}
@@ -105,6 +105,70 @@ END;
         }
         conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
     }
+
+    {
+        let pooled = pool.get()?;
+        pooled.ping()?;
+        assert_eq!(pool.get_busy_count()?, 1);
+
+        let mut dropped = pool.get()?;
+        assert_eq!(pool.get_busy_count()?, 2);
+        dropped.drop_session();
+        dropped.release()?;
+        assert_eq!(pool.get_busy_count()?, 1);
+
+        let mut closed = pool.get()?;
+        assert_eq!(pool.get_busy_count()?, 2);
+        closed.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+        assert_eq!(pool.get_busy_count()?, 1);
+        closed.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+        drop(closed);
+        assert_eq!(pool.get_busy_count()?, 1);
+    }
+    assert_eq!(pool.get_busy_count()?, 0);
+
+    {
+        let tag = "NLS_DATE_FORMAT=YYYY-MM-DD";
+        let mut init_calls = 0;
+
+        {
+            let _tagged = pool.get_tagged(tag, |conn, found_tag| {
+                init_calls += 1;
+                assert_ne!(found_tag, tag);
+                let alter = conn.prepare_stmt(
+                    Some("alter session set nls_date_format = 'YYYY-MM-DD'"),
+                    None,
+                    false,
+                )?;
+                alter.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+                Ok(())
+            })?;
+        }
+
+        {
+            let _tagged = pool.get_tagged(tag, |_, _| {
+                init_calls += 1;
+                Ok(())
+            })?;
+        }
+
+        assert_eq!(init_calls, 1);
+    }
+
+    let stats = pool.stats()?;
+    assert_eq!(*stats.busy_count(), 0);
+    assert_eq!(*stats.stmt_cache_size(), 100);
+    assert_eq!(*stats.checkouts(), 6);
+    assert_eq!(*stats.checkout_latency().count(), 6);
+    assert_eq!(*stats.checkout_timeouts(), 0);
+
+    let text = stats.to_prometheus(&[("pool", "test")]);
+    assert!(text.contains("# TYPE mimir_pool_checkouts_total counter\n"));
+    assert!(text.contains("mimir_pool_checkouts_total{pool=\"test\"} 6\n"));
+    assert!(
+        text.contains("mimir_pool_checkout_duration_seconds_bucket{pool=\"test\",le=\"+Inf\"} 6\n")
+    );
+
     pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
 
     Ok(())
@@ -114,3 +178,39 @@ END;
 fn pool() {
     check_with_ctxt!(pool_res)
 }
+
+fn pool_timeouts_res(ctxt: &Context) -> Result<()> {
+    let mut pcp = ctxt.init_pool_create_params()?;
+    pcp.set_min_sessions(1);
+    pcp.set_max_sessions(1);
+    pcp.set_session_increment(0);
+    pcp.set_get_mode(enums::ODPIPoolGetMode::NoWait);
+
+    let pool = Pool::create(
+        ctxt,
+        Some(&CREDS[0]),
+        Some(&CREDS[1]),
+        Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
+        None,
+        Some(pcp),
+    )?;
+
+    {
+        let _busy = pool.get()?;
+        assert!(pool.get().is_err());
+    }
+
+    let stats = pool.stats()?;
+    assert_eq!(*stats.checkouts(), 1);
+    assert_eq!(*stats.checkout_timeouts(), 1);
+    assert_eq!(*stats.checkout_errors(), 0);
+
+    pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
+
+    Ok(())
+}
+
+#[test]
+fn pool_timeouts() {
+    check_with_ctxt!(pool_timeouts_res)
+}
@@ -0,0 +1,33 @@
+use mimir::r2d2::OracleConnectionManager;
+use mimir::{Context, Result};
+use r2d2_crate::Pool;
+use CREDS;
+
+fn r2d2_res(_ctxt: &Context) -> Result<()> {
+    let manager = OracleConnectionManager::new(
+        Context::create()?,
+        Some(&CREDS[0]),
+        Some(&CREDS[1]),
+        Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
+    )?;
+    let pool = Pool::builder()
+        .max_size(2)
+        .build(manager)
+        .expect("unable to build pool");
+
+    {
+        let conn = pool.get().expect("unable to get connection");
+        conn.ping()?;
+        assert!(!conn.is_broken());
+    }
+
+    assert_eq!(pool.state().connections, 2);
+    assert_eq!(pool.state().idle_connections, 2);
+
+    Ok(())
+}
+
+#[test]
+fn r2d2() {
+    check_with_ctxt!(r2d2_res)
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, TimeZone, Utc};
+use mimir::serde::{from_row, to_values};
+use mimir::{Row, Value};
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Address {
+    street: String,
+    zip: Option<i32>,
+}
+
+#[derive(Serialize)]
+struct NewUser {
+    username: String,
+    user_id: u32,
+    #[serde(with = "mimir::serde::timestamp")]
+    created: DateTime<Utc>,
+    #[serde(with = "mimir::serde::timestamp::option")]
+    updated: Option<DateTime<Utc>>,
+    email: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct User {
+    username: String,
+    user_id: i64,
+    created: DateTime<Utc>,
+    email: Option<String>,
+    address: Address,
+}
+
+fn columns() -> Vec<String> {
+    [
+        "USERNAME", "USER_ID", "CREATED", "EMAIL", "ADDRESS", "COMMON",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect()
+}
+
+fn row() -> Row {
+    Row::new(vec![
+        Value::String("jozias".to_string()),
+        Value::Double(42.0),
+        Value::Timestamp(Utc.ymd(2017, 11, 3).and_hms(12, 30, 0)),
+        Value::Null,
+        Value::Object(vec![
+            ("STREET".to_string(), Value::String("Main".to_string())),
+            ("ZIP".to_string(), Value::Int64(12345)),
+        ]),
+        Value::String("NO".to_string()),
+    ])
+}
+
+#[test]
+fn struct_from_row() {
+    let user: User = from_row(row(), &columns()).expect("from_row failed");
+    assert_eq!(
+        user,
+        User {
+            username: "jozias".to_string(),
+            user_id: 42,
+            created: Utc.ymd(2017, 11, 3).and_hms(12, 30, 0),
+            email: None,
+            address: Address {
+                street: "Main".to_string(),
+                zip: Some(12345),
+            },
+        }
+    );
+}
+
+#[test]
+fn tuple_from_row() {
+    let row = Row::new(vec![Value::String("jozias".to_string()), Value::Null]);
+    let names = vec!["USERNAME".to_string(), "EMAIL".to_string()];
+    let tuple: (String, Option<String>) = from_row(row, &names).expect("from_row failed");
+    assert_eq!(tuple, ("jozias".to_string(), None));
+}
+
+#[test]
+fn missing_column() {
+    let row = Row::new(vec![Value::String("jozias".to_string())]);
+    let names = vec!["USERNAME".to_string()];
+    match from_row::<User>(row, &names) {
+        Ok(_) => assert!(false),
+        Err(e) => assert_eq!(
+            format!("{}", e),
+            "Unable to deserialize the row: missing field `user_id`!"
+        ),
+    }
+}
+
+#[test]
+fn values_from_struct() {
+    let user = NewUser {
+        username: "jozias".to_string(),
+        user_id: 42,
+        created: Utc.ymd(2017, 11, 3).and_hms(12, 30, 0),
+        updated: None,
+        email: Some("2017-11-03T12:30:00Z".to_string()),
+    };
+    let values = to_values(&user).expect("to_values failed");
+    assert_eq!(
+        values,
+        vec![
+            ("username".to_string(), Value::String("jozias".to_string())),
+            ("user_id".to_string(), Value::Int64(42)),
+            (
+                "created".to_string(),
+                Value::Timestamp(Utc.ymd(2017, 11, 3).and_hms(12, 30, 0)),
+            ),
+            ("updated".to_string(), Value::Null),
+            (
+                "email".to_string(),
+                Value::String("2017-11-03T12:30:00Z".to_string()),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn values_from_non_struct() {
+    match to_values(&(1, 2)) {
+        Ok(_) => assert!(false),
+        Err(e) => assert_eq!(
+            format!("{}", e),
+            "Unable to serialize the value: only a struct or a map can be bound!"
+        ),
+    }
+    match to_values(&Some(vec![1, 2])) {
+        Ok(_) => assert!(false),
+        Err(e) => assert_eq!(
+            format!("{}", e),
+            "Unable to serialize the value: only a struct or a map can be bound!"
+        ),
+    }
+}
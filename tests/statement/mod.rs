@@ -1,11 +1,15 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use mimir::enums::ODPIFetchMode::Last;
-use mimir::enums::ODPINativeTypeNum::{Bytes, Double, Int64};
-use mimir::enums::ODPIOracleTypeNum::{Number, Varchar};
+use mimir::enums::ODPINativeTypeNum::{Bytes, Double, Int64, Lob, Timestamp};
+use mimir::enums::ODPIOracleTypeNum::{Blob, Clob, Number, RowID, TimestampTz, Varchar};
 use mimir::enums::ODPIStatementType::Insert;
+use mimir::enums::{ODPIFetchMode, ODPINativeTypeNum, ODPIOracleTypeNum};
 use mimir::flags;
 use mimir::Result;
 use mimir::{
-    Connection, Context, Data, ODPIBytes, ODPIDataBuffer, ODPIStr, QueryInfo, TypeInfo, Var,
+    BatchResult, Column, Connection, Context, Data, ErrorInfo, FromSql, JsonBuilder, JsonOptions,
+    ODPIBytes, ODPIDataBuffer, ODPIDataTypeInfo, ODPIStr, QueryInfo, Row, SqlType, TypeInfo, Value,
+    Var, YearsMonths,
 };
 use rand::{self, Rng};
 use std::convert::TryFrom;
@@ -150,6 +154,26 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
     cols = bind_by_value_pos.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
     assert_eq!(cols, 2);
 
+    // bind_struct / execute test
+    #[derive(Serialize)]
+    struct Username {
+        username: String,
+    }
+
+    let bind_struct = conn.prepare_stmt(
+        Some(
+            "select * from username \
+             where username = :username",
+        ),
+        None,
+        false,
+    )?;
+    bind_struct.bind_struct(&Username {
+        username: "jozias".to_string(),
+    })?;
+    cols = bind_struct.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    assert_eq!(cols, 2);
+
     // execute / fetch test
     let fetch = conn.prepare_stmt(
         Some("select * from username where username = :username"),
@@ -197,6 +221,9 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
             _ => assert!(false),
         }
     }
+    let repeated = conn.prepare_stmt(Some("select :a, :b, :a from dual"), None, false)?;
+    let bind_count = repeated.get_bind_count()?;
+    assert_eq!(repeated.get_bind_names(bind_count)?, vec!["A", "B"]);
     let error_count = bn.get_batch_error_count()?;
     assert_eq!(error_count, 0);
     let info = bn.get_info()?;
@@ -204,7 +231,7 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
     assert_eq!(info.statement_type(), Insert);
 
     // execute /fetch_rows / get_row_count / scroll test
-    let all_users = conn.prepare_stmt(Some("select * from username"), None, false)?;
+    let all_users = conn.prepare_stmt(Some("select * from username"), None, true)?;
     let au_cols = all_users.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
     assert_eq!(au_cols, 2);
     all_users.fetch_rows(10)?;
@@ -238,6 +265,150 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
 
     em.execute_many(flags::DPI_MODE_EXEC_DEFAULT, 2)?;
 
+    // execute_batch test
+    let batch = em.execute_batch(2)?;
+    assert!(batch.is_success());
+    assert_eq!(batch.successes(), &vec![0, 1]);
+    assert_eq!(batch.row_counts(), &vec![1, 1]);
+    assert!(em.execute_batch(65_537).is_err());
+
+    // execute_returning / execute_many_returning test
+    let returned = conn.execute_returning(
+        "insert into username values (:id, :username) returning id, username into :rid, :run",
+        &[&rng.gen::<i32>().abs(), &"jozias"],
+        &[SqlType::Int64, SqlType::Varchar(256)],
+    )?;
+    assert_eq!(returned.len(), 1);
+    assert_eq!(returned[0].len(), 2);
+    assert_eq!(returned[0].get::<String>(1)?, "jozias");
+
+    // the returning types are converted to by the database, or the statement fails
+    let id = rng.gen::<i32>().abs();
+    let returned = conn.execute_returning(
+        "insert into username values (:id, :username) returning id into :rid",
+        &[&id, &"jozias"],
+        &[SqlType::Varchar(40)],
+    )?;
+    assert_eq!(returned[0].get::<String>(0)?, id.to_string());
+    assert!(conn
+        .execute_returning(
+            "insert into username values (:id, :username) returning username into :run",
+            &[&rng.gen::<i32>().abs(), &"jozias"],
+            &[SqlType::Int64],
+        )
+        .is_err());
+
+    let ids = [rng.gen::<i32>().abs(), rng.gen::<i32>().abs()];
+    let returned = conn.execute_many_returning(
+        "insert into username values (:id, :username) returning id into :rid",
+        &[&[&ids[0], &"jozias"], &[&ids[1], &None::<String>]],
+        &[SqlType::Int64],
+    )?;
+    assert_eq!(returned.len(), 2);
+    assert_eq!(returned[0][0].get::<i32>(0)?, ids[0]);
+    assert_eq!(returned[1][0].get::<i32>(0)?, ids[1]);
+
+    // query / nested cursor test
+    let mut rows = conn.query(
+        "select :1, cursor(select level from dual connect by level <= 3) from dual",
+        &[&"jozias"],
+    )?;
+    assert_eq!(rows.column_names().len(), 2);
+    let columns = rows.statement().columns()?;
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns[1].type_name(), "REF CURSOR");
+    assert_eq!(columns[1].rust_type(), "Statement");
+    rows.set_fetch_array_size(10)?;
+    let row = rows.next().expect("no row")?;
+    assert_eq!(row.get::<String>(0)?, "jozias");
+    let cursor = row.get_cursor(1)?;
+    cursor.set_fetch_array_size(2)?;
+    let levels = cursor
+        .map(|row| row.and_then(|row| row.get::<i64>(0)))
+        .collect::<Result<Vec<i64>>>()?;
+    assert_eq!(levels, vec![1, 2, 3]);
+    assert!(rows.next().is_none());
+
+    // timestamp with time zone test
+    let mut rows = conn.query(
+        "select to_timestamp_tz('2017-11-03 14:30:00 +02:00', 'YYYY-MM-DD HH24:MI:SS TZH:TZM') \
+         from dual",
+        &[],
+    )?;
+    let row = rows.next().expect("no row")?;
+    assert_eq!(
+        row.get::<DateTime<Utc>>(0)?,
+        Utc.ymd(2017, 11, 3).and_hms(12, 30, 0)
+    );
+
+    // LOB and ROWID test
+    let mut rows = conn.query(
+        "select to_clob('jozias') as txt, to_blob(hextoraw('DEADBEEF')) as bin, rowid as id \
+         from username where username = 'jozias'",
+        &[],
+    )?;
+    let row = rows.next().expect("no row")?;
+    assert_eq!(row.get::<String>(0)?, "jozias");
+    assert_eq!(row.get::<Vec<u8>>(1)?, vec![0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(row.get::<String>(2)?.len(), 18);
+    assert!(rows.next().is_none());
+
+    // write_json / write_ndjson test
+    let mut json = Vec::new();
+    let mut rows = conn.query(
+        "select level as lvl, to_clob('ab\"c') as txt, hextoraw('DEADBEEF') as bin \
+         from dual connect by level <= 2",
+        &[],
+    )?;
+    assert_eq!(rows.write_json(&mut json, &JsonOptions::default())?, 2);
+    assert_eq!(
+        String::from_utf8_lossy(&json),
+        r#"[{"LVL":1,"TXT":"ab\"c","BIN":"3q2+7w=="},{"LVL":2,"TXT":"ab\"c","BIN":"3q2+7w=="}]"#
+    );
+    json.clear();
+    let mut rows = conn.query("select to_clob('abcdef') as txt from dual", &[])?;
+    let options = JsonBuilder::default()
+        .lob_limit(Some(3))
+        .build()
+        .expect("invalid options");
+    assert_eq!(rows.write_ndjson(&mut json, &options)?, 1);
+    assert_eq!(String::from_utf8_lossy(&json), "{\"TXT\":\"abc\"}\n");
+
+    // query_scrollable test
+    let mut scrollable = conn.query_scrollable(
+        "select level from dual connect by level <= 5 order by level",
+        &[],
+    )?;
+    assert_eq!(scrollable.num_rows()?, 5);
+    assert_eq!(scrollable.position(), 5);
+    let level = |row: Option<Row>| row.map(|row| row.get::<i64>(0)).unwrap_or(Ok(0));
+    assert_eq!(level(scrollable.first()?)?, 1);
+    assert!(scrollable.prior()?.is_none());
+    assert_eq!(level(scrollable.absolute(3)?)?, 3);
+    assert_eq!(level(scrollable.relative(-1)?)?, 2);
+    assert_eq!(level(scrollable.next_row()?)?, 3);
+    assert_eq!(level(scrollable.last()?)?, 5);
+    assert!(scrollable.relative(1)?.is_none());
+    assert_eq!(level(scrollable.prior()?)?, 4);
+    assert_eq!(scrollable.position(), 4);
+
+    // prepare_cached / statement_cache_stats test
+    conn.set_statement_cache_size(1)?;
+    for _ in 0..2 {
+        let cached = conn.prepare_cached("select * from username")?;
+        assert_eq!(cached.execute(flags::DPI_MODE_EXEC_DEFAULT)?, 2);
+    }
+    {
+        let cached = conn.prepare_cached("select username from username")?;
+        assert_eq!(cached.execute(flags::DPI_MODE_EXEC_DEFAULT)?, 1);
+    }
+    let stats = conn.statement_cache_stats();
+    assert_eq!(*stats.capacity(), 1);
+    assert_eq!(*stats.size(), 1);
+    assert_eq!(*stats.hits(), 1);
+    assert_eq!(*stats.misses(), 2);
+    assert_eq!(*stats.evictions(), 1);
+
     bind_by_pos.close(None)?;
     bind_by_value_name.close(None)?;
     bind_by_value_pos.close(None)?;
@@ -256,3 +427,164 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
 fn statement() {
     check_with_ctxt!(stmt_res)
 }
+
+#[test]
+fn batch_result() {
+    let error = |offset| {
+        ErrorInfo::new(
+            1,
+            offset,
+            "ORA-00001: unique constraint violated".to_string(),
+            "dpiStmt_executeMany".to_string(),
+            "execute".to_string(),
+            "23000".to_string(),
+            false,
+        )
+    };
+    let batch = BatchResult::new(4, vec![1, 0, 2, 0], vec![error(3), error(1)]);
+
+    assert_eq!(batch.len(), 4);
+    assert!(!batch.is_success());
+    assert_eq!(batch.successes(), &vec![0, 2]);
+    assert_eq!(batch.row_counts(), &vec![1, 0, 2, 0]);
+    assert_eq!(batch.total_row_count(), 3);
+    let failed = batch
+        .failures()
+        .iter()
+        .map(|&(row, ref info)| (row, info.code()))
+        .collect::<Vec<(usize, i32)>>();
+    assert_eq!(failed, vec![(1, 1), (3, 1)]);
+}
+
+#[test]
+fn row() {
+    let row = Row::new(vec![Value::Int64(1), Value::Null]);
+
+    assert_eq!(row.len(), 2);
+    assert_eq!(row.get::<i64>(0).expect("invalid value"), 1);
+    assert_eq!(row.get::<Option<String>>(1).expect("invalid value"), None);
+    assert_eq!(row.value(1).expect("invalid value"), &Value::Null);
+    match row.get::<i64>(2) {
+        Ok(_) => assert!(false),
+        Err(e) => assert_eq!(format!("{}", e), "No column at index 2!"),
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn fetch_mode() {
+    assert_eq!(ODPIFetchMode::Absolte, ODPIFetchMode::Absolute);
+    assert_eq!(ODPIFetchMode::Absolte as u32, 32);
+}
+
+#[test]
+fn columns() {
+    let column = |oracle_type_num, native_type_num, precision, scale, fs_precision| {
+        let type_info = TypeInfo::new(ODPIDataTypeInfo {
+            oracle_type_num,
+            default_native_type_num: native_type_num,
+            size_in_chars: 256,
+            db_size_in_bytes: 1024,
+            precision,
+            scale,
+            fs_precision,
+            ..Default::default()
+        });
+        Column::new("COL", &type_info, true).expect("invalid column")
+    };
+
+    let varchar = column(Varchar, Bytes, 0, 0, 0);
+    assert_eq!(varchar.name(), "COL");
+    assert_eq!(varchar.type_name(), "VARCHAR2(256 CHAR)");
+    assert_eq!(varchar.rust_type(), "String");
+    assert_eq!(*varchar.size(), 256);
+    assert!(*varchar.nullable());
+    assert_eq!(*varchar.object_type(), None);
+
+    let byte_varchar = TypeInfo::new(ODPIDataTypeInfo {
+        oracle_type_num: Varchar,
+        default_native_type_num: Bytes,
+        size_in_chars: 30,
+        db_size_in_bytes: 30,
+        ..Default::default()
+    });
+    assert_eq!(
+        Column::new("COL", &byte_varchar, true)
+            .expect("invalid column")
+            .type_name(),
+        "VARCHAR2(30 BYTE)"
+    );
+
+    let interval_ym = column(
+        ODPIOracleTypeNum::IntervalYM,
+        ODPINativeTypeNum::IntervalYM,
+        2,
+        0,
+        0,
+    );
+    assert_eq!(interval_ym.type_name(), "INTERVAL YEAR(2) TO MONTH");
+    assert_eq!(interval_ym.rust_type(), "YearsMonths");
+    assert_eq!(column(Clob, Lob, 0, 0, 0).rust_type(), "String");
+    assert_eq!(column(Blob, Lob, 0, 0, 0).rust_type(), "Vec<u8>");
+    assert_eq!(
+        column(RowID, ODPINativeTypeNum::Rowid, 0, 0, 0).rust_type(),
+        "String"
+    );
+
+    assert_eq!(column(Number, Double, 10, 2, 0).type_name(), "NUMBER(10,2)");
+    assert_eq!(column(Number, Int64, 10, 0, 0).rust_type(), "i64");
+    assert_eq!(column(Number, Double, 0, -127, 0).type_name(), "NUMBER");
+    assert_eq!(
+        column(Number, Double, 126, -127, 0).type_name(),
+        "FLOAT(126)"
+    );
+    assert_eq!(
+        column(TimestampTz, Timestamp, 0, 0, 6).type_name(),
+        "TIMESTAMP(6) WITH TIME ZONE"
+    );
+    assert_eq!(
+        column(TimestampTz, Timestamp, 0, 0, 6).rust_type(),
+        "DateTime<Utc>"
+    );
+}
+
+#[test]
+fn value_json() {
+    let json = |value: Value| value.to_json().expect("invalid value");
+
+    assert_eq!(json(Value::Null), "null");
+    assert_eq!(json(Value::Boolean(true)), "true");
+    assert_eq!(json(Value::Int64(-1)), "-1");
+    assert_eq!(json(Value::Double(1.5)), "1.5");
+    assert_eq!(json(Value::Double(::std::f64::NAN)), "null");
+    assert_eq!(
+        json(Value::String("a\"b\\c\n\u{1}".to_string())),
+        r#""a\"b\\c\n\u0001""#
+    );
+    assert_eq!(json(Value::Bytes(b"mimir".to_vec())), r#""bWltaXI=""#);
+    assert_eq!(
+        json(Value::Timestamp(Utc.ymd(2017, 11, 3).and_hms(12, 30, 0))),
+        r#""2017-11-03T12:30:00+00:00""#
+    );
+    assert_eq!(
+        json(Value::IntervalDS(
+            Duration::days(1) + Duration::hours(2) + Duration::milliseconds(3500)
+        )),
+        r#""P1DT2H0M3.5S""#
+    );
+    let mut interval = YearsMonths::default();
+    interval.set_years(-1);
+    interval.set_months(-2);
+    assert_eq!(json(Value::IntervalYM(interval)), r#""-P1Y2M""#);
+    assert_eq!(
+        YearsMonths::from_sql(Value::IntervalYM(interval)).expect("invalid interval"),
+        interval
+    );
+    assert_eq!(
+        json(Value::Object(vec![
+            ("STREET".to_string(), Value::String("Main".to_string())),
+            ("ZIP".to_string(), Value::Null),
+        ])),
+        r#"{"STREET":"Main","ZIP":null}"#
+    );
+}
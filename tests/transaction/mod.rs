@@ -0,0 +1,156 @@
+use mimir::enums::ODPIVisibility::{Immediate, OnCommit};
+use mimir::flags;
+use mimir::Connection;
+use mimir::Context;
+use mimir::Result;
+use CREDS;
+
+const SETUP: [&str; 3] = [
+    "create or replace type mimir_msg as object (id number)",
+    "begin
+       dbms_aqadm.create_queue_table(queue_table => 'mimir_msg_qt', queue_payload_type => 'mimir_msg');
+       dbms_aqadm.create_queue(queue_name => 'mimir_msg_q', queue_table => 'mimir_msg_qt', max_retries => 5);
+       dbms_aqadm.start_queue(queue_name => 'mimir_msg_q');
+     end;",
+    "declare
+       enq_opts dbms_aq.enqueue_options_t;
+       msg_props dbms_aq.message_properties_t;
+       msg_id raw(16);
+     begin
+       dbms_aq.enqueue('mimir_msg_q', enq_opts, msg_props, mimir_msg(42), msg_id);
+       commit;
+     end;",
+];
+
+const TEARDOWN: [&str; 2] = [
+    "begin
+       dbms_aqadm.stop_queue(queue_name => 'mimir_msg_q');
+       dbms_aqadm.drop_queue(queue_name => 'mimir_msg_q');
+       dbms_aqadm.drop_queue_table(queue_table => 'mimir_msg_qt');
+     end;",
+    "drop type mimir_msg",
+];
+
+fn execute_all(conn: &Connection, sqls: &[&str]) -> Result<()> {
+    for sql in sqls {
+        let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+        stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    }
+    Ok(())
+}
+
+fn transaction_res(ctxt: &Context) -> Result<()> {
+    let mut ccp = ctxt.init_common_create_params()?;
+    ccp.set_encoding("UTF-8")?;
+    ccp.set_nchar_encoding("UTF-8")?;
+
+    let conn = Connection::create(
+        ctxt,
+        Some(&CREDS[0]),
+        Some(&CREDS[1]),
+        Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
+        Some(ccp),
+        None,
+    )?;
+
+    {
+        let txn = conn.begin_transaction();
+        let stmt = txn.connection().prepare_stmt(
+            Some("update username set username = username where 1 = 0"),
+            None,
+            false,
+        )?;
+        stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+        txn.commit()?;
+    }
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn transaction() {
+    check_with_ctxt!(transaction_res)
+}
+
+fn process_rollback_res(ctxt: &Context) -> Result<()> {
+    let conn = Connection::create(
+        ctxt,
+        Some(&CREDS[0]),
+        Some(&CREDS[1]),
+        Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
+        None,
+        None,
+    )?;
+    execute_all(&conn, &SETUP)?;
+
+    let payload = conn.get_object_type("MIMIR_MSG")?.create()?;
+    let enqueue_opts = conn.new_enq_options()?;
+    let dequeue_opts = conn.new_deq_options()?;
+    let msg_props = conn.new_msg_props()?;
+    dequeue_opts.set_wait(0)?;
+
+    {
+        let txn = conn.begin_transaction();
+        enqueue_opts.set_visibility(Immediate)?;
+        assert!(txn
+            .enqueue_object("mimir_msg_q", &enqueue_opts, &msg_props, &payload)
+            .is_err());
+
+        dequeue_opts.set_visibility(Immediate)?;
+        assert!(txn
+            .deque_object("mimir_msg_q", &dequeue_opts, &msg_props, &payload)
+            .is_err());
+        dequeue_opts.set_visibility(OnCommit)?;
+
+        txn.rollback()?;
+    }
+
+    let mut first = None;
+    let failed = conn.begin_transaction().process(
+        "mimir_msg_q",
+        &dequeue_opts,
+        &msg_props,
+        &payload,
+        |_, msg_id, _, props| {
+            first = Some((msg_id.to_string(), props.get_num_attempts()?));
+            Err("processing failed".into())
+        },
+    );
+    assert!(failed.is_err());
+    let (first_id, first_attempts) = first.expect("no message dequeued");
+
+    conn.begin_transaction().process(
+        "mimir_msg_q",
+        &dequeue_opts,
+        &msg_props,
+        &payload,
+        |_, msg_id, _, props| {
+            assert_eq!(msg_id, first_id);
+            assert_eq!(props.get_num_attempts()?, first_attempts + 1);
+            Ok(())
+        },
+    )?;
+
+    assert!(conn
+        .begin_transaction()
+        .process(
+            "mimir_msg_q",
+            &dequeue_opts,
+            &msg_props,
+            &payload,
+            |_, _, _, _| Ok(())
+        )
+        .is_err());
+
+    execute_all(&conn, &TEARDOWN)?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn process_rollback() {
+    check_with_ctxt!(process_rollback_res)
+}
@@ -2,7 +2,7 @@ use mimir::enums::ODPINativeTypeNum::{Bytes, Int64};
 use mimir::enums::ODPIOracleTypeNum::{Number, Varchar};
 use mimir::flags;
 use mimir::Result;
-use mimir::{Connection, Context, Data, ODPIData};
+use mimir::{Connection, Context, Data, FromSql, ODPIData, SqlType, Value, Var};
 use std::convert::TryFrom;
 use CREDS;
 
@@ -39,6 +39,33 @@ fn var_res(ctxt: &Context) -> Result<()> {
         }
     }
 
+    let typed = Var::builder::<i64>().max_array_size(2).create(&conn)?;
+    assert_eq!(typed.sql_type(), Some(SqlType::Int64));
+    typed.set(0, &42)?;
+    typed.set(1, &None::<i64>)?;
+    assert_eq!(typed.get::<i64>(0)?, 42);
+    assert_eq!(typed.get::<String>(0)?, "42");
+    assert_eq!(typed.get::<Option<i64>>(1)?, None);
+    assert!(typed.get::<i64>(1).is_err());
+
+    let names = Var::from_slice(&conn, &["jozias", "mimir"])?;
+    assert_eq!(names.sql_type(), Some(SqlType::Varchar(6)));
+    assert_eq!(names.get_num_elements_in_array()?, 2);
+    assert_eq!(names.get::<String>(1)?, "mimir");
+
+    let (jozias, mimir) = ("jozias".to_string(), "mimir".to_string());
+    let refs = Var::from_slice(&conn, &[&jozias, &mimir])?;
+    assert_eq!(refs.sql_type(), Some(SqlType::Varchar(6)));
+    assert_eq!(refs.get::<String>(0)?, "jozias");
+
+    let counts = Var::from_slice(&conn, &[&1_i64, &2_i64])?;
+    assert_eq!(counts.sql_type(), Some(SqlType::Int64));
+    assert_eq!(counts.get::<i64>(1)?, 2);
+
+    let empty = Var::from_slice::<&i64>(&conn, &[])?;
+    assert_eq!(empty.sql_type(), Some(SqlType::Int64));
+    assert_eq!(empty.get_num_elements_in_array()?, 0);
+
     conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
 
     Ok(())
@@ -48,3 +75,31 @@ fn var_res(ctxt: &Context) -> Result<()> {
 fn variable() {
     check_with_ctxt!(var_res)
 }
+
+#[test]
+fn from_sql() {
+    assert_eq!(i64::from_sql(Value::Int64(7)).expect("i64"), 7);
+    assert_eq!(i32::from_sql(Value::Double(7.0)).expect("i32"), 7);
+    assert!(u8::from_sql(Value::Int64(300)).is_err());
+    assert!(i64::from_sql(Value::Double(7.5)).is_err());
+    assert_eq!(
+        i64::from_sql(Value::Double(-9_223_372_036_854_775_808.0)).expect("i64"),
+        i64::min_value()
+    );
+    assert!(i64::from_sql(Value::Double(9_223_372_036_854_775_808.0)).is_err());
+    assert!(i64::from_sql(Value::Double(-1e19)).is_err());
+    assert!(i64::from_sql(Value::Double(::std::f64::INFINITY)).is_err());
+    assert!(i32::from_sql(Value::Double(1e300)).is_err());
+    assert_eq!(
+        f64::from_sql(Value::String(" 1.5 ".to_string())).expect("f64"),
+        1.5
+    );
+    assert_eq!(String::from_sql(Value::Int64(7)).expect("String"), "7");
+    assert_eq!(
+        Vec::<u8>::from_sql(Value::Bytes(vec![1, 2])).expect("Vec<u8>"),
+        vec![1, 2]
+    );
+    assert_eq!(Option::<bool>::from_sql(Value::Null).expect("Option"), None);
+    assert!(bool::from_sql(Value::Null).is_err());
+    assert!(Value::Null.is_null());
+}